@@ -84,8 +84,42 @@ udfs=(
     "vinCleaner"
     "vinManuf"
     "vinYear"
+    "vinDecode"
+    "vinIsValid"
+    "vinCountry"
+    "vinBrand"
+    "vinGroup"
+    "vinExtractAll"
+    "vinRepair"
+    "vinYearAt"
+    "vinYearCandidates"
+    "vinDecodeVpic"
+    "vinPseudonymize"
+    "vinClassify"
+    "vinDecodeLegacy"
+    "vinGenerate"
     "extractUrl"
     "hasUrl"
+    "extractUrlParts"
+    "extractAllUrls"
+    "urlRegisteredDomain"
+    "urlPublicSuffix"
+    "urlSubdomain"
+    "urlCanonicalize"
+    "urlToAscii"
+    "urlToUnicode"
+    "urlIsHomograph"
+    "urlUnwrap"
+    "urlRefang"
+    "urlDefang"
+    "hasIndicator"
+    "extractIps"
+    "ipInCidrList"
+    "extractEmails"
+    "emailIsValid"
+    "emailNormalize"
+    "extractLinks"
+    "urlCategory"
     "arrayTopK"
     "extractPhone"
     "tiktokenCount"
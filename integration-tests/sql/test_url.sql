@@ -25,3 +25,112 @@ SELECT 'Test 7: hasUrl without URL' AS test_name, hasUrl('no url here') AS resul
 
 SELECT 'Test 8: hasUrl ftp protocol' AS test_name, hasUrl('ftp://example.org') AS result;
 -- Expected: true
+
+-- Test extractUrlParts
+SELECT 'Test 9: extractUrlParts host' AS test_name, extractUrlParts('GET https://api.example.com:8443/v1/items?id=42 200').host AS result;
+-- Expected: api.example.com
+
+SELECT 'Test 10: extractUrlParts query' AS test_name, extractUrlParts('GET https://api.example.com:8443/v1/items?id=42 200').query['id'] AS result;
+-- Expected: 42
+
+-- Test extractAllUrls
+SELECT 'Test 11: extractAllUrls' AS test_name, extractAllUrls('ftp://a.org then http://b.org') AS result;
+-- Expected: ['ftp://a.org','http://b.org']
+
+-- Test URL boundaries
+SELECT 'Test 12: extractUrl punctuation and parentheses' AS test_name, extractUrl('(see https://example.org/foo).') AS result;
+-- Expected: https://example.org/foo
+
+SELECT 'Test 13: extractUrl Markdown link' AS test_name, extractUrl('[docs](https://example.org/docs)') AS result;
+-- Expected: https://example.org/docs
+
+-- Test scheme registry and bare domains
+SELECT 'Test 14: hasUrl bare domain off by default' AS test_name, hasUrl('docs at www.example.com/path') AS result;
+-- Expected: 0
+
+SELECT 'Test 15: hasUrl file name' AS test_name, hasUrl('open file.txt or v1.2') AS result;
+-- Expected: 0
+
+SELECT 'Test 16: extractUrl mailto' AS test_name, extractUrl('write to mailto:team@example.org.') AS result;
+-- Expected: mailto:team@example.org
+
+-- Test Public Suffix List domains
+SELECT 'Test 17: urlRegisteredDomain' AS test_name, urlRegisteredDomain('https://shop.example.co.uk/cart') AS result;
+-- Expected: example.co.uk
+
+SELECT 'Test 18: urlRegisteredDomain private suffix' AS test_name, urlRegisteredDomain('https://alice.github.io/blog') AS result;
+-- Expected: alice.github.io
+
+SELECT 'Test 19: urlPublicSuffix' AS test_name, urlPublicSuffix('https://news.blogspot.co.uk/') AS result;
+-- Expected: blogspot.co.uk
+
+SELECT 'Test 20: urlSubdomain' AS test_name, urlSubdomain('https://a.b.example.com.au/') AS result;
+-- Expected: a.b
+
+-- Test urlCanonicalize
+SELECT 'Test 21: urlCanonicalize' AS test_name, urlCanonicalize('HTTPS://Example.COM:443/a/./b/../c?utm_source=x&b=2&a=1#top') AS result;
+-- Expected: https://example.com/a/c?a=1&b=2
+
+SELECT 'Test 22: urlCanonicalize variants' AS test_name, uniqExact(urlCanonicalize(arrayJoin(['http://example.org/p?b=2&a=1', 'http://EXAMPLE.org:80/p?a=1&b=2&fbclid=x', 'http://example.org/x/../p?a=1&b=2#top']))) AS result;
+-- Expected: 1
+
+-- Test internationalized domain names
+SELECT 'Test 23: urlToAscii' AS test_name, urlToAscii('https://münchen.de/karte') AS result;
+-- Expected: https://xn--mnchen-3ya.de/karte
+
+SELECT 'Test 24: urlToUnicode' AS test_name, urlToUnicode('https://xn--mnchen-3ya.de/karte') AS result;
+-- Expected: https://münchen.de/karte
+
+SELECT 'Test 25: urlIsHomograph' AS test_name, urlIsHomograph('https://аррӏе.com/login') = 'true' AS result;
+-- Expected: 1
+
+-- Test urlUnwrap
+SELECT 'Test 26: urlUnwrap Google' AS test_name, urlUnwrap('https://www.google.com/url?q=https%3A%2F%2Fexample.org%2Fa&sa=D') AS result;
+-- Expected: https://example.org/a
+
+SELECT 'Test 27: urlUnwrap SafeLinks' AS test_name, urlUnwrap('https://eur01.safelinks.protection.outlook.com/?url=https%3A%2F%2Fexample.org%2Fdoc&data=05') AS result;
+-- Expected: https://example.org/doc
+
+-- Test defanged indicators
+SELECT 'Test 28: urlRefang' AS test_name, urlRefang('hxxps://evil[.]com/payload') AS result;
+-- Expected: https://evil.com/payload
+
+SELECT 'Test 29: urlDefang' AS test_name, urlDefang('beacon to https://evil.com/payload') AS result;
+-- Expected: beacon to hxxps://evil[.]com/payload
+
+SELECT 'Test 30: hasIndicator' AS test_name, hasIndicator('blocked 1.2.3[.]4') = 'true' AS result;
+-- Expected: 1
+
+-- Test IP addresses
+SELECT 'Test 31: extractIps' AS test_name, extractIps('from 10.0.0.1:22 to [2001:DB8:0::1]:443') AS result;
+-- Expected: ['10.0.0.1','2001:db8::1']
+
+SELECT 'Test 32: ipInCidrList private' AS test_name, ipInCidrList('connection from 192.168.1.20') = 'true' AS result;
+-- Expected: 1
+
+SELECT 'Test 33: ipInCidrList public' AS test_name, ipInCidrList('connection from 8.8.8.8') = 'true' AS result;
+-- Expected: 0
+
+-- Test email addresses
+SELECT 'Test 34: extractEmails' AS test_name, extractEmails('Contact <john.doe@example.com>, sales@example.co.uk.') AS result;
+-- Expected: ['john.doe@example.com','sales@example.co.uk']
+
+SELECT 'Test 35: emailIsValid' AS test_name, emailIsValid('user@bücher.de') = 'true' AS result;
+-- Expected: 1
+
+SELECT 'Test 36: emailNormalize Gmail' AS test_name, emailNormalize('John.Doe+news@GoogleMail.com') AS result;
+-- Expected: johndoe@gmail.com
+
+-- Test extractLinks
+SELECT 'Test 37: extractLinks HTML' AS test_name, extractLinks('<p><a href="../docs/">The <b>docs</b></a> <img src="logo.png" alt="Logo"></p>', 'https://example.org/blog/post') AS result;
+-- Expected: [('https://example.org/docs/','The docs','html-link'),('https://example.org/blog/logo.png','Logo','html-image')]
+
+SELECT 'Test 38: extractLinks Markdown' AS test_name, extractLinks('See [the guide](guide.md "Guide") and ![chart](https://example.org/c.png)', '') AS result;
+-- Expected: [('guide.md','the guide','markdown-link'),('https://example.org/c.png','chart','markdown-image')]
+
+-- Test urlCategory
+SELECT 'Test 39: urlCategory video' AS test_name, urlCategory('https://www.youtube.com/watch?v=dQw4w9WgXcQ') AS result;
+-- Expected: video
+
+SELECT 'Test 40: urlCategory public suffix' AS test_name, urlCategory('https://www.amazon.co.uk/dp/B0') AS result;
+-- Expected: ecommerce
@@ -32,3 +32,86 @@ SELECT 'Test 9: vinYear 2012' AS test_name, vinYear('JM1BL1M72C1587426') AS resu
 
 SELECT 'Test 10: vinYear 2011' AS test_name, vinYear('1FTEW1CM9BFA74557') AS result;
 -- Expected: 2011
+
+-- Test vinDecode
+SELECT 'Test 11: vinDecode manufacturer' AS test_name, JSONExtractString(vinDecode('1GKKRNED9EJ262581'), 'manufacturer') AS result;
+-- Expected: General Motors USA
+
+SELECT 'Test 12: vinDecode model year' AS test_name, JSONExtractInt(vinDecode('JM1BL1M72C1587426'), 'model_year') AS result;
+-- Expected: 2012
+
+SELECT 'Test 13: vinDecode country' AS test_name, JSONExtractString(vinDecode(' wf05xxgcc5fj00000 '), 'country') AS result;
+-- Expected: Germany
+
+-- Test vinIsValid
+SELECT 'Test 14: vinIsValid valid check digit' AS test_name, vinIsValid('1GKKRNED9EJ262581') AS result;
+-- Expected: true
+
+SELECT 'Test 15: vinIsValid wrong check digit' AS test_name, vinIsValid('1GKKRNED8EJ262581') AS result;
+-- Expected: false
+
+SELECT 'Test 16: vinIsValid European VIN' AS test_name, vinIsValid('WF05XXGCC5FJ00000') AS result;
+-- Expected: true
+
+-- Test vinCountry
+SELECT 'Test 17: vinCountry Germany' AS test_name, tupleElement(vinCountry('WF05XXGCC5FJ00000'), 'code') AS result;
+-- Expected: DE
+
+SELECT 'Test 18: vinCountry United Kingdom' AS test_name, tupleElement(vinCountry('SALGA2HF0EA000000'), 'name') AS result;
+-- Expected: United Kingdom
+
+-- Test vinBrand
+SELECT 'Test 19: vinBrand' AS test_name, vinBrand('TRUZZZSNZY1063363') AS result;
+-- Expected: Audi
+
+-- Test vinGroup
+SELECT 'Test 20: vinGroup' AS test_name, vinGroup('TRUZZZSNZY1063363') AS result;
+-- Expected: Volkswagen Group
+
+-- Test vinExtractAll
+SELECT 'Test 21: vinExtractAll' AS test_name, vinExtractAll('Trade-in 1GKKRNED9EJ262581, new WF0-5XXGCC-5FJ00000') AS result;
+-- Expected: ['1GKKRNED9EJ262581','WF05XXGCC5FJ00000']
+
+-- Test vinExtractAll without VIN
+SELECT 'Test 22: vinExtractAll empty' AS test_name, length(vinExtractAll('no vin here')) AS result;
+-- Expected: 0
+
+-- Test vinRepair
+SELECT 'Test 23: vinRepair corrected' AS test_name, tupleElement(vinRepair('1GKKRNED9EJ262S81'), 'vin') AS result;
+-- Expected: 1GKKRNED9EJ262581
+
+-- Test vinRepair status
+SELECT 'Test 24: vinRepair unchanged' AS test_name, tupleElement(vinRepair('1GKKRNED9EJ262581'), 'status') AS result;
+-- Expected: unchanged
+
+-- Test vinYearAt
+SELECT 'Test 25: vinYearAt' AS test_name, vinYearAt('JM1BL1M72C1587426', toDate('2005-06-01')) AS result;
+-- Expected: 1982
+
+-- Test vinYearCandidates
+SELECT 'Test 26: vinYearCandidates' AS test_name, vinYearCandidates('JM1BL1M72C1587426', '2024') AS result;
+-- Expected: [1982,2012]
+
+-- Test vinDecode VDS fields
+SELECT 'Test 27: vinDecode model' AS test_name, JSONExtractString(vinDecode('1FTEW1CM9BFA74557'), 'model') AS result;
+-- Expected: F-150
+
+-- Test vinDecodeVpic without a vPIC index (WMI fallback)
+SELECT 'Test 28: vinDecodeVpic fallback' AS test_name, JSONExtractString(vinDecodeVpic('TRUZZZSNZY1063363'), 'make') AS result;
+-- Expected: Audi
+
+-- Test vinPseudonymize keeps the manufacturer
+SELECT 'Test 29: vinPseudonymize' AS test_name, vinManuf(vinPseudonymize('1GKKRNED9EJ262581')) AS result;
+-- Expected: General Motors USA
+
+-- Test vinClassify
+SELECT 'Test 30: vinClassify' AS test_name, vinClassify('124379N123456') AS result;
+-- Expected: legacy
+
+-- Test vinDecodeLegacy
+SELECT 'Test 31: vinDecodeLegacy' AS test_name, JSONExtractString(vinDecodeLegacy('124379N123456'), 'make') AS result;
+-- Expected: Chevrolet
+
+-- Test vinGenerate produces valid VINs of the requested WMI and year
+SELECT 'Test 32: vinGenerate' AS test_name, countIf(vinIsValid(v) = 'true' AND vinYearAt(v, '2024') = '2015' AND substring(v, 1, 3) = '1GK') AS result FROM (SELECT vinGenerate(number, '1GK', '2015') AS v FROM numbers(100));
+-- Expected: 100
@@ -26,6 +26,9 @@ struct Project {
 
 const IGNORED: [&str; 2] = ["shared", "string"];
 
+// Helper tools shipped with a project that are not UDFs
+const IGNORED_BINS: [&str; 1] = ["vin-vpic-convert"];
+
 struct ToClickHouseFunctionName;
 
 impl tera::Filter for ToClickHouseFunctionName {
@@ -66,6 +69,11 @@ fn get_bins(member: String) -> Result<Vec<Bin>> {
     let bins = manifest
         .bin
         .into_iter()
+        .filter(|bin| {
+            bin.name
+                .as_deref()
+                .is_none_or(|name| !IGNORED_BINS.contains(&name))
+        })
         .map(|bin| {
             let config = get_bin_config(member.clone()).unwrap_or_default();
 
@@ -0,0 +1,123 @@
+//! Formatting helpers for ClickHouse composite values in `TabSeparated` output.
+//!
+//! UDFs that return `Array`, `Tuple` or `Map` types write one value per line in
+//! ClickHouse's text representation, e.g. `['a','b']` or `('US','United States')`.
+//! String elements must be quoted with [`quote`]; numbers can be written as-is.
+//!
+//! # Examples
+//!
+//! ```
+//! use shared::format::{array, quote, tuple};
+//!
+//! assert_eq!(array(["a", "b"].map(quote)), "['a','b']");
+//! assert_eq!(tuple([quote("US"), quote("United States")]), "('US','United States')");
+//! assert_eq!(array([tuple([quote("x"), "1".to_string()])]), "[('x',1)]");
+//! ```
+
+/// Quotes a string as a ClickHouse string literal.
+///
+/// Backslashes, single quotes, tabs and newlines are escaped so that the value
+/// stays on a single `TabSeparated` line.
+///
+/// # Examples
+///
+/// ```
+/// use shared::format::quote;
+///
+/// assert_eq!(quote("abc"), "'abc'");
+/// assert_eq!(quote("Côte d'Ivoire"), r"'Côte d\'Ivoire'");
+/// assert_eq!(quote("a\tb\\c"), r"'a\tb\\c'");
+/// ```
+pub fn quote<S: AsRef<str>>(s: S) -> String {
+    let s = s.as_ref();
+    let mut quoted = String::with_capacity(s.len() + 2);
+
+    quoted.push('\'');
+    for c in s.chars() {
+        match c {
+            '\\' => quoted.push_str("\\\\"),
+            '\'' => quoted.push_str("\\'"),
+            '\t' => quoted.push_str("\\t"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('\'');
+
+    quoted
+}
+
+/// Formats already serialized values as a ClickHouse `Array`.
+///
+/// # Examples
+///
+/// ```
+/// use shared::format::array;
+///
+/// assert_eq!(array(["1", "2"]), "[1,2]");
+/// assert_eq!(array(Vec::<String>::new()), "[]");
+/// ```
+pub fn array<I, S>(values: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    format!("[{}]", join(values))
+}
+
+/// Formats already serialized values as a ClickHouse `Tuple`.
+///
+/// # Examples
+///
+/// ```
+/// use shared::format::tuple;
+///
+/// assert_eq!(tuple(["'a'", "1"]), "('a',1)");
+/// ```
+pub fn tuple<I, S>(values: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    format!("({})", join(values))
+}
+
+/// Formats already serialized key/value pairs as a ClickHouse `Map`.
+///
+/// # Examples
+///
+/// ```
+/// use shared::format::{map, quote};
+///
+/// assert_eq!(map([(quote("a"), quote("1"))]), "{'a':'1'}");
+/// ```
+pub fn map<I, K, V>(pairs: I) -> String
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+{
+    let pairs = pairs
+        .into_iter()
+        .map(|(k, v)| format!("{}:{}", k.as_ref(), v.as_ref()));
+
+    format!("{{{}}}", join(pairs))
+}
+
+fn join<I, S>(values: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut joined = String::new();
+
+    for (i, value) in values.into_iter().enumerate() {
+        if i > 0 {
+            joined.push(',');
+        }
+        joined.push_str(value.as_ref());
+    }
+
+    joined
+}
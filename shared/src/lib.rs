@@ -1,4 +1,6 @@
+pub mod format;
 pub mod io;
+pub mod table;
 
 #[cfg(test)]
 mod tests {
@@ -65,4 +67,45 @@ mod tests {
         assert!(f("").is_none());
         assert!(f("error_input").is_none());
     }
+
+    #[test]
+    fn test_format_nested_values() {
+        use super::format::{array, map, quote, tuple};
+
+        let rows = [("a", 1), ("b'c", 2)]
+            .iter()
+            .map(|(s, n)| tuple([quote(s), n.to_string()]));
+        assert_eq!(array(rows), r"[('a',1),('b\'c',2)]");
+
+        assert_eq!(
+            map([(quote("k"), array([quote("v1"), quote("v2")]))]),
+            "{'k':['v1','v2']}"
+        );
+        assert_eq!(quote("line\nbreak"), r"'line\nbreak'");
+    }
+
+    #[test]
+    fn test_global_table() {
+        use super::table::GlobalTable;
+        use std::path::Path;
+
+        fn load_file(words: &mut Vec<String>, path: &Path) -> anyhow::Result<usize> {
+            let content = std::fs::read_to_string(path)?;
+            words.extend(content.lines().map(String::from));
+            Ok(content.lines().count())
+        }
+
+        static WORDS: GlobalTable<Vec<String>> =
+            GlobalTable::new("Word list", "SHARED_TEST_WORDS_PATH", Vec::new, load_file);
+
+        let path = std::env::temp_dir().join("shared_test_global_table.txt");
+        std::fs::write(&path, "a\nb\n").unwrap();
+        assert!(WORDS.init(["/nonexistent/words.txt"]).is_err());
+        WORDS.init([&path]).unwrap();
+        assert_eq!(WORDS.get(), &["a", "b"]);
+
+        let error = WORDS.init([&path]).unwrap_err();
+        assert_eq!(error.to_string(), "Word list is already initialized");
+        std::fs::remove_file(&path).unwrap();
+    }
 }
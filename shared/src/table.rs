@@ -0,0 +1,125 @@
+//! Global lookup tables for UDF binaries, extended with user files.
+//!
+//! A table starts from a base value (usually data embedded in the binary), then
+//! loads the files listed in an environment variable (separated like `PATH`),
+//! then the files passed as arguments to the binary, which take precedence.
+//!
+//! The table must be initialized before its first lookup. Otherwise, the first
+//! lookup builds it from the base value and the environment variable; errors
+//! loading those files are logged to stderr and the base value is used alone.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! use anyhow::Result;
+//! use shared::table::GlobalTable;
+//!
+//! #[derive(Default)]
+//! struct Words(Vec<String>);
+//!
+//! impl Words {
+//!     fn load_file(&mut self, path: &Path) -> Result<usize> {
+//!         let content = std::fs::read_to_string(path)?;
+//!         self.0.extend(content.lines().map(String::from));
+//!         Ok(content.lines().count())
+//!     }
+//! }
+//!
+//! static WORDS: GlobalTable<Words> =
+//!     GlobalTable::new("Word list", "WORDS_PATH", Words::default, Words::load_file);
+//!
+//! WORDS.init(["/etc/words.txt"]).unwrap();
+//! assert!(!WORDS.get().0.is_empty());
+//! ```
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use anyhow::{bail, Result};
+
+/// A global table, built once from a base value and files.
+pub struct GlobalTable<T> {
+    table: OnceLock<T>,
+    /// Name used in error messages, e.g. `URL categorizer`.
+    name: &'static str,
+    /// Environment variable listing the files to load.
+    env: &'static str,
+    base: fn() -> T,
+    load_file: fn(&mut T, &Path) -> Result<usize>,
+}
+
+impl<T> GlobalTable<T> {
+    /// Creates an uninitialized table.
+    ///
+    /// `load_file` adds the entries of a file to the table, and must leave it
+    /// unchanged on error.
+    pub const fn new(
+        name: &'static str,
+        env: &'static str,
+        base: fn() -> T,
+        load_file: fn(&mut T, &Path) -> Result<usize>,
+    ) -> Self {
+        GlobalTable {
+            table: OnceLock::new(),
+            name,
+            env,
+            base,
+            load_file,
+        }
+    }
+
+    /// Initializes the table from the environment variable and the given files.
+    ///
+    /// # Errors
+    ///
+    /// Fails if a file cannot be loaded, or if the table is already initialized.
+    pub fn init<I, P>(&self, paths: I) -> Result<()>
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let mut table = self.load_from_env()?;
+        for path in paths {
+            (self.load_file)(&mut table, path.as_ref())?;
+        }
+
+        self.set(table)
+    }
+
+    /// Initializes the table with a value built by the caller, e.g. from
+    /// [`load_from_env`](Self::load_from_env) and other arguments.
+    pub fn set(&self, table: T) -> Result<()> {
+        if self.table.set(table).is_err() {
+            bail!("{} is already initialized", self.name);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the table, built from the base value and the environment
+    /// variable if it was not initialized.
+    pub fn get(&self) -> &T {
+        self.table.get_or_init(|| {
+            self.load_from_env().unwrap_or_else(|e| {
+                eprintln!("ERROR: {:#}", e);
+                (self.base)()
+            })
+        })
+    }
+
+    /// Builds a table from the base value and the files listed in the
+    /// environment variable.
+    pub fn load_from_env(&self) -> Result<T> {
+        let mut table = (self.base)();
+
+        if let Some(paths) = std::env::var_os(self.env) {
+            for path in std::env::split_paths(&paths).filter(|p| !p.as_os_str().is_empty()) {
+                (self.load_file)(&mut table, &path)?;
+            }
+        }
+
+        Ok(table)
+    }
+}
@@ -0,0 +1,9 @@
+use anyhow::Result;
+use shared::io::process_stdin;
+use url::email::email_is_valid;
+
+fn main() -> Result<()> {
+    process_stdin(Box::new(email_is_valid));
+
+    Ok(())
+}
@@ -0,0 +1,9 @@
+use anyhow::Result;
+use shared::io::process_stdin;
+use url::email::email_normalize;
+
+fn main() -> Result<()> {
+    process_stdin(Box::new(email_normalize));
+
+    Ok(())
+}
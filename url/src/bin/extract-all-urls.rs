@@ -0,0 +1,30 @@
+use anyhow::Result;
+use shared::format::{array, quote, tuple};
+use shared::io::{args, process_stdin};
+use url::url::{extract_all_urls, init_url_detector, UrlDetector};
+
+fn main() -> Result<()> {
+    // `distinct` drops repeated URLs, `offsets` returns
+    // Array(Tuple(url String, offset UInt64)) instead of Array(String). Extra
+    // schemes can be passed as arguments, and `bare-domains` turns on the
+    // detection of host names without a scheme.
+    let args = args();
+    init_url_detector(UrlDetector::from_args(&args))?;
+    let distinct = args.iter().any(|arg| arg == "distinct");
+    let offsets = args.iter().any(|arg| arg == "offsets");
+
+    process_stdin(Box::new(move |s| {
+        let urls = extract_all_urls(s, distinct);
+
+        Some(if offsets {
+            array(
+                urls.iter()
+                    .map(|(url, offset)| tuple([quote(url), offset.to_string()])),
+            )
+        } else {
+            array(urls.iter().map(|(url, _)| quote(url)))
+        })
+    }));
+
+    Ok(())
+}
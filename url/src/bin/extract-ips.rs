@@ -0,0 +1,15 @@
+use anyhow::Result;
+use shared::format::{array, quote};
+use shared::io::{args, process_stdin};
+use url::ip::extract_ips;
+
+fn main() -> Result<()> {
+    // `distinct` drops repeated addresses
+    let distinct = args().iter().any(|arg| arg == "distinct");
+
+    process_stdin(Box::new(move |s| {
+        Some(array(extract_ips(s, distinct).iter().map(quote)))
+    }));
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use anyhow::Result;
+use shared::format::{array, quote, tuple};
+use shared::io::process_stdin;
+use url::links::extract_links;
+
+fn main() -> Result<()> {
+    process_stdin(Box::new(|s| {
+        // The base URL is an optional second column
+        let (document, base) = match s.split_once('\t') {
+            Some((document, base)) => (document, Some(base)),
+            None => (s, None),
+        };
+
+        Some(array(extract_links(document, base).iter().map(|link| {
+            tuple([
+                quote(&link.url),
+                quote(&link.text),
+                quote(link.kind.as_str()),
+            ])
+        })))
+    }));
+
+    Ok(())
+}
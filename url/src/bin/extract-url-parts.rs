@@ -0,0 +1,14 @@
+use anyhow::Result;
+use shared::io::{args, process_stdin};
+use url::parts::extract_url_parts;
+use url::url::{init_url_detector, UrlDetector};
+
+fn main() -> Result<()> {
+    // Extra schemes can be passed as arguments, and `bare-domains` turns on the
+    // detection of host names without a scheme
+    init_url_detector(UrlDetector::from_args(&args()))?;
+
+    process_stdin(Box::new(extract_url_parts));
+
+    Ok(())
+}
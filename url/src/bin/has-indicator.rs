@@ -0,0 +1,14 @@
+use anyhow::Result;
+use shared::io::{args, process_stdin};
+use url::defang::has_indicator;
+use url::url::{init_url_detector, UrlDetector};
+
+fn main() -> Result<()> {
+    // Extra schemes can be passed as arguments. Host names without a scheme are
+    // always indicators
+    init_url_detector(UrlDetector::from_args(&args()))?;
+
+    process_stdin(Box::new(has_indicator));
+
+    Ok(())
+}
@@ -1,8 +1,12 @@
 use anyhow::Result;
-use shared::io::process_stdin;
-use url::url::has_url;
+use shared::io::{args, process_stdin};
+use url::url::{has_url, init_url_detector, UrlDetector};
 
 fn main() -> Result<()> {
+    // Extra schemes can be passed as arguments, and `bare-domains` turns on the
+    // detection of host names without a scheme
+    init_url_detector(UrlDetector::from_args(&args()))?;
+
     process_stdin(Box::new(has_url));
 
     Ok(())
@@ -0,0 +1,12 @@
+use anyhow::Result;
+use shared::io::{args, process_stdin};
+use url::cidr::{init_cidr_list, ip_in_cidr_list};
+
+fn main() -> Result<()> {
+    // Ranges and list files are passed as arguments
+    init_cidr_list(&args())?;
+
+    process_stdin(Box::new(ip_in_cidr_list));
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use anyhow::Result;
+use shared::io::{args, process_stdin};
+use url::canonical::{init_canonicalizer, url_canonicalize, Canonicalizer};
+
+fn main() -> Result<()> {
+    // Extra tracking parameters and `no-default-params` can be passed as arguments
+    init_canonicalizer(Canonicalizer::from_args(&args()))?;
+
+    process_stdin(Box::new(url_canonicalize));
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use anyhow::Result;
+use shared::io::{args, process_stdin};
+use url::category::{init_categorizer, url_category};
+
+fn main() -> Result<()> {
+    // Category files overriding the embedded list can be passed as arguments
+    init_categorizer(args())?;
+
+    process_stdin(Box::new(url_category));
+
+    Ok(())
+}
@@ -0,0 +1,9 @@
+use anyhow::Result;
+use shared::io::process_stdin;
+use url::idn::url_is_homograph;
+
+fn main() -> Result<()> {
+    process_stdin(Box::new(url_is_homograph));
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use anyhow::Result;
+use shared::io::{args, process_stdin};
+use url::domain::url_public_suffix;
+use url::psl::init_public_suffix_list;
+
+fn main() -> Result<()> {
+    // `icann-only` and the path of a newer public suffix list can be passed as arguments
+    init_public_suffix_list(&args())?;
+
+    process_stdin(Box::new(url_public_suffix));
+
+    Ok(())
+}
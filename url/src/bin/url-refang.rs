@@ -0,0 +1,9 @@
+use anyhow::Result;
+use shared::io::process_stdin;
+use url::defang::url_refang;
+
+fn main() -> Result<()> {
+    process_stdin(Box::new(url_refang));
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+use anyhow::Result;
+use shared::io::{args, process_stdin};
+use url::unwrap::{init_unwrapper, url_unwrap};
+
+fn main() -> Result<()> {
+    // Extra rule files can be passed as arguments
+    init_unwrapper(args())?;
+
+    process_stdin(Box::new(url_unwrap));
+
+    Ok(())
+}
@@ -0,0 +1,498 @@
+//! URL canonicalization for deduplication.
+//!
+//! Variants of the same URL are rewritten to one canonical form, following the
+//! normalizations of RFC 3986 section 6 and removing tracking parameters:
+//!
+//! - The scheme and host are lowercased, and a trailing dot of the host is removed
+//! - Internationalized hosts are converted to Punycode, so `münchen.de` and
+//!   `xn--mnchen-3ya.de` are the same host
+//! - The default port of the scheme is removed (`:443` for `https`)
+//! - Percent-encoding is normalized: unreserved characters (`A-Z a-z 0-9 - . _ ~`)
+//!   are decoded, other escapes are uppercased, and characters that cannot
+//!   appear in URLs are encoded
+//! - Dot segments (`/./`, `/../`) are resolved, and an empty path becomes `/`
+//! - Tracking parameters are removed, and the other parameters are sorted by name,
+//!   keeping the order of repeated names
+//! - The fragment is dropped
+//!
+//! # Tracking Parameters
+//!
+//! [`DEFAULT_TRACKING_PARAMS`] are removed by default, a trailing `*` matching
+//! any suffix. The `url-canonicalize` binary takes more parameters as arguments,
+//! and `no-default-params` to only remove those:
+//!
+//! ```xml
+//! <command>url-canonicalize ref share_* no-default-params</command>
+//! ```
+//!
+//! # Examples
+//!
+//! ```
+//! use url::canonical::url_canonicalize;
+//!
+//! assert_eq!(
+//!     url_canonicalize("HTTPS://Example.COM:443/a/./b/../c?utm_source=x&b=2&a=1#top").unwrap(),
+//!     "https://example.com/a/c?a=1&b=2"
+//! );
+//! ```
+
+use anyhow::{bail, Result};
+use std::sync::OnceLock;
+
+use crate::idn::host_to_ascii;
+use crate::parts::percent_decode;
+use crate::url::detect_url;
+
+/// Query parameters added by analytics and ad platforms, removed by default
+pub const DEFAULT_TRACKING_PARAMS: &[&str] = &[
+    "utm_*",
+    "fbclid",
+    "gclid",
+    "gclsrc",
+    "dclid",
+    "gbraid",
+    "wbraid",
+    "msclkid",
+    "yclid",
+    "twclid",
+    "ttclid",
+    "li_fat_id",
+    "igshid",
+    "mc_cid",
+    "mc_eid",
+    "_ga",
+    "_gl",
+    "_hsenc",
+    "_hsmi",
+    "mkt_tok",
+    "oly_anon_id",
+    "oly_enc_id",
+    "vero_id",
+    "wickedid",
+];
+
+/// Schemes and their default ports
+const DEFAULT_PORTS: &[(&str, u16)] = &[
+    ("http", 80),
+    ("https", 443),
+    ("ws", 80),
+    ("wss", 443),
+    ("ftp", 21),
+    ("ftps", 990),
+    ("sftp", 22),
+    ("ssh", 22),
+    ("git", 9418),
+];
+
+static CANONICALIZER: OnceLock<Canonicalizer> = OnceLock::new();
+
+/// Sets the canonicalizer used by [`url_canonicalize`].
+///
+/// # Errors
+///
+/// Fails if the canonicalizer was already initialized.
+pub fn init_canonicalizer(canonicalizer: Canonicalizer) -> Result<()> {
+    if CANONICALIZER.set(canonicalizer).is_err() {
+        bail!("URL canonicalizer is already initialized");
+    }
+
+    Ok(())
+}
+
+/// Returns the global canonicalizer, the default one unless set with [`init_canonicalizer`].
+fn canonicalizer() -> &'static Canonicalizer {
+    CANONICALIZER.get_or_init(Canonicalizer::default)
+}
+
+/// Rewrites URLs to their canonical form.
+#[derive(Debug, Clone)]
+pub struct Canonicalizer {
+    /// Names of the query parameters to remove, a trailing `*` matching any suffix.
+    tracking_params: Vec<String>,
+}
+
+impl Default for Canonicalizer {
+    fn default() -> Self {
+        Canonicalizer {
+            tracking_params: DEFAULT_TRACKING_PARAMS
+                .iter()
+                .map(|p| p.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl Canonicalizer {
+    /// Creates a canonicalizer removing [`DEFAULT_TRACKING_PARAMS`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a canonicalizer from the arguments of a UDF binary.
+    ///
+    /// Other arguments are more parameters to remove. With `no-default-params`,
+    /// [`DEFAULT_TRACKING_PARAMS`] are not removed, only the listed parameters.
+    pub fn from_args(args: &[String]) -> Self {
+        let mut canonicalizer = Self::default();
+        if args.iter().any(|arg| arg == "no-default-params") {
+            canonicalizer.tracking_params.clear();
+        }
+
+        for arg in args.iter().filter(|arg| *arg != "no-default-params") {
+            canonicalizer = canonicalizer.with_tracking_param(arg);
+        }
+
+        canonicalizer
+    }
+
+    /// Adds a query parameter to remove, e.g. `ref` or `share_*`.
+    pub fn with_tracking_param(mut self, param: &str) -> Self {
+        self.tracking_params.push(param.to_ascii_lowercase());
+        self
+    }
+
+    /// Returns whether a query parameter is a tracking parameter.
+    fn is_tracking_param(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+
+        self.tracking_params
+            .iter()
+            .any(|param| match param.strip_suffix('*') {
+                Some(prefix) => name.starts_with(prefix),
+                None => name == *param,
+            })
+    }
+
+    /// Canonicalizes a URL.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - A URL, with or without scheme
+    ///
+    /// # Returns
+    ///
+    /// The canonical URL. URLs without authority (`mailto:`) only have their
+    /// scheme lowercased, their encoding normalized and their fragment dropped.
+    pub fn canonicalize(&self, url: &str) -> String {
+        let url = url.split_once('#').map_or(url, |(url, _)| url);
+
+        let (scheme, rest) = match url.split_once(':') {
+            // Bare domains only have dots before a port
+            Some((scheme, rest)) if is_scheme(scheme) && !scheme.contains('.') => {
+                (scheme.to_ascii_lowercase(), rest)
+            }
+            _ => (String::new(), url),
+        };
+        let Some(rest) = rest
+            .strip_prefix("//")
+            .or(scheme.is_empty().then_some(rest))
+        else {
+            return format!("{}:{}", scheme, normalize_percent(rest));
+        };
+
+        let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let (authority, path) = match rest.find('/') {
+            Some(i) => rest.split_at(i),
+            None => (rest, ""),
+        };
+
+        let mut canonical = String::with_capacity(url.len());
+        if !scheme.is_empty() {
+            canonical.push_str(&scheme);
+            canonical.push_str("://");
+        }
+        canonical.push_str(&self.authority(&scheme, authority));
+        canonical.push_str(&remove_dot_segments(&normalize_percent(path)));
+
+        let query = self.query(query);
+        if !query.is_empty() {
+            canonical.push('?');
+            canonical.push_str(&query);
+        }
+
+        canonical
+    }
+
+    /// Converts the host to lowercase ASCII and removes a default port.
+    fn authority(&self, scheme: &str, authority: &str) -> String {
+        let (userinfo, host_port) = match authority.rsplit_once('@') {
+            Some((userinfo, host_port)) => (Some(userinfo), host_port),
+            None => (None, authority),
+        };
+        let (host, port) = match host_port.rsplit_once(':') {
+            Some((host, port)) if !port.contains(']') => (host, port),
+            _ => (host_port, ""),
+        };
+
+        let default_port = DEFAULT_PORTS
+            .iter()
+            .find(|(s, _)| *s == scheme)
+            .is_some_and(|(_, default)| port.parse() == Ok(*default));
+
+        let mut canonical = String::new();
+        if let Some(userinfo) = userinfo {
+            canonical.push_str(&normalize_percent(userinfo));
+            canonical.push('@');
+        }
+        let host = host.trim_end_matches('.');
+        let host = host_to_ascii(&percent_decode(host))
+            .unwrap_or_else(|| normalize_percent(&host.to_lowercase()));
+        canonical.push_str(&host);
+        if !port.is_empty() && !default_port {
+            canonical.push(':');
+            canonical.push_str(port);
+        }
+
+        canonical
+    }
+
+    /// Removes tracking parameters and sorts the others by name.
+    fn query(&self, query: &str) -> String {
+        let mut params: Vec<(String, String)> = query
+            .split('&')
+            .filter(|param| !param.is_empty())
+            .map(|param| {
+                let (name, value) = param
+                    .split_once('=')
+                    .map_or((param, None), |(n, v)| (n, Some(v)));
+                let name = normalize_percent(name);
+                let param = match value {
+                    Some(value) => format!("{}={}", name, normalize_percent(value)),
+                    None => name.clone(),
+                };
+                (name, param)
+            })
+            .filter(|(name, _)| !self.is_tracking_param(name))
+            .collect();
+
+        params.sort_by(|a, b| a.0.cmp(&b.0));
+
+        params
+            .into_iter()
+            .map(|(_, param)| param)
+            .collect::<Vec<_>>()
+            .join("&")
+    }
+}
+
+fn is_scheme(scheme: &str) -> bool {
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Decodes escaped unreserved characters, uppercases other escapes, and encodes
+/// characters not allowed in URLs.
+fn normalize_percent(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut normalized = String::with_capacity(s.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escaped = match bytes[i] {
+            b'%' if i + 2 < bytes.len()
+                && bytes[i + 1].is_ascii_hexdigit()
+                && bytes[i + 2].is_ascii_hexdigit() =>
+            {
+                u8::from_str_radix(&s[i + 1..i + 3], 16).ok()
+            }
+            _ => None,
+        };
+
+        match escaped {
+            Some(byte) if is_unreserved(byte) => normalized.push(byte as char),
+            Some(byte) => normalized.push_str(&format!("%{:02X}", byte)),
+            None if is_allowed(bytes[i]) => normalized.push(bytes[i] as char),
+            None => normalized.push_str(&format!("%{:02X}", bytes[i])),
+        }
+        i += if escaped.is_some() { 3 } else { 1 };
+    }
+
+    normalized
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+/// Returns whether a byte may appear unencoded in a URL, apart from `%` of escapes.
+fn is_allowed(byte: u8) -> bool {
+    byte.is_ascii_graphic()
+        && !matches!(
+            byte,
+            b'"' | b'%' | b'<' | b'>' | b'\\' | b'^' | b'`' | b'{' | b'|' | b'}'
+        )
+}
+
+/// Resolves `.` and `..` segments of a path, as in RFC 3986 section 5.2.4.
+pub(crate) fn remove_dot_segments(path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').skip(1).collect();
+    let mut resolved: Vec<&str> = Vec::with_capacity(segments.len());
+
+    for (i, segment) in segments.iter().enumerate() {
+        let last = i + 1 == segments.len();
+        match *segment {
+            "." => {}
+            ".." => {
+                resolved.pop();
+            }
+            segment => {
+                resolved.push(segment);
+                continue;
+            }
+        }
+        // A trailing dot segment leaves a directory path
+        if last {
+            resolved.push("");
+        }
+    }
+
+    format!("/{}", resolved.join("/"))
+}
+
+/// Finds the URL in a text and returns its canonical form.
+///
+/// See the [module documentation](self) for the normalizations. Tracking
+/// parameters are configured with [`init_canonicalizer`].
+///
+/// # Arguments
+///
+/// * `s` - The input string to search for a URL
+///
+/// # Returns
+///
+/// * `Some(String)` - The canonical URL
+/// * `None` - If no URL is found
+///
+/// # Examples
+///
+/// ```
+/// use url::canonical::url_canonicalize;
+///
+/// assert_eq!(
+///     url_canonicalize("http://www.Example.com:80/%7euser/a%2fb?fbclid=abc").unwrap(),
+///     "http://www.example.com/~user/a%2Fb"
+/// );
+/// assert_eq!(
+///     url_canonicalize("https://www.example.com/shop?q=caf%c3%a9&gclid=1#reviews").unwrap(),
+///     "https://www.example.com/shop?q=caf%C3%A9"
+/// );
+/// assert!(url_canonicalize("no url here").is_none());
+/// ```
+pub fn url_canonicalize(s: &str) -> Option<String> {
+    let (start, end) = detect_url(s)?;
+    Some(canonicalizer().canonicalize(&s[start..end]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_variants() {
+        let canonical = "https://example.com/products/42?color=red&size=m";
+        for variant in [
+            "https://example.com/products/42?color=red&size=m",
+            "HTTPS://EXAMPLE.COM/products/42?size=m&color=red",
+            "https://example.com:443/products/42?color=red&size=m#reviews",
+            "https://example.com./shop/../products/./42?utm_source=news&color=red&size=m",
+            "https://example.com/%70roducts/42?color=r%65d&size=m&utm_campaign=spring",
+            "https://example.com/products/42?fbclid=x&color=red&gclid=y&size=m&",
+        ] {
+            assert_eq!(
+                Canonicalizer::new().canonicalize(variant),
+                canonical,
+                "{}",
+                variant
+            );
+        }
+
+        let canonical = "https://xn--mnchen-3ya.de/a";
+        for variant in [
+            "https://münchen.de/a",
+            "https://MÜNCHEN.DE./a",
+            "https://xn--mnchen-3ya.de/a",
+            "https://XN--MNCHEN-3YA.DE:443/a",
+            "https://m%C3%BCnchen.de/a",
+            "https://m%c3%bcnchen.de/a",
+        ] {
+            assert_eq!(
+                Canonicalizer::new().canonicalize(variant),
+                canonical,
+                "{}",
+                variant
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonicalize_components() {
+        let c = Canonicalizer::new();
+        assert_eq!(c.canonicalize("http://Example.org"), "http://example.org/");
+        assert_eq!(
+            c.canonicalize("http://example.org:8080/"),
+            "http://example.org:8080/"
+        );
+        assert_eq!(
+            c.canonicalize("https://example.org:80/"),
+            "https://example.org:80/"
+        );
+        assert_eq!(
+            c.canonicalize("http://User@Example.org/"),
+            "http://User@example.org/"
+        );
+        assert_eq!(c.canonicalize("http://[::1]:80/a"), "http://[::1]/a");
+        assert_eq!(c.canonicalize("http://[::1]/a"), "http://[::1]/a");
+        assert_eq!(c.canonicalize("http://A%2fB.org/"), "http://a%2Fb.org/");
+        assert_eq!(c.canonicalize("Example.org:8080/a"), "example.org:8080/a");
+        assert_eq!(c.canonicalize("file:///etc/./hosts"), "file:///etc/hosts");
+        assert_eq!(
+            c.canonicalize("http://a.org/café"),
+            "http://a.org/caf%C3%A9"
+        );
+        assert_eq!(
+            c.canonicalize("http://a.org/a%2F%zz"),
+            "http://a.org/a%2F%25zz"
+        );
+        assert_eq!(
+            c.canonicalize("http://a.org/?b=1&a=2&b=0"),
+            "http://a.org/?a=2&b=1&b=0"
+        );
+        assert_eq!(
+            c.canonicalize("http://a.org/?flag&UTM_Medium=x"),
+            "http://a.org/?flag"
+        );
+        assert_eq!(
+            c.canonicalize("MAILTO:Team@Example.org#x"),
+            "mailto:Team@Example.org"
+        );
+    }
+
+    #[test]
+    fn test_remove_dot_segments() {
+        assert_eq!(remove_dot_segments(""), "/");
+        assert_eq!(remove_dot_segments("/a/b/c/./../../g"), "/a/g");
+        assert_eq!(remove_dot_segments("/mid/content=5/../6"), "/mid/6");
+        assert_eq!(remove_dot_segments("/a/b/.."), "/a/");
+        assert_eq!(remove_dot_segments("/a/."), "/a/");
+        assert_eq!(remove_dot_segments("/../a"), "/a");
+        assert_eq!(remove_dot_segments("/a//b/"), "/a//b/");
+    }
+
+    #[test]
+    fn test_tracking_params_from_args() {
+        let args = ["ref", "share_*", "no-default-params"].map(String::from);
+        let c = Canonicalizer::from_args(&args);
+        assert_eq!(
+            c.canonicalize("http://a.org/?ref=hn&share_id=1&utm_source=x"),
+            "http://a.org/?utm_source=x"
+        );
+
+        let c = Canonicalizer::from_args(&["ref".to_string()]);
+        assert_eq!(
+            c.canonicalize("http://a.org/?ref=hn&utm_source=x&q=1"),
+            "http://a.org/?q=1"
+        );
+    }
+}
@@ -0,0 +1,275 @@
+//! URL categorization with domain lists.
+//!
+//! [`url_category`] labels the host of a URL with a category such as `social`,
+//! `search`, `shortener`, `video`, `ecommerce`, `cdn` or `advertising`, from
+//! the embedded `src/url_categories.csv` list:
+//!
+//! ```text
+//! domain,category
+//! youtube.com,video
+//! amazon.*,ecommerce
+//! ```
+//!
+//! - `domain` - A domain, matching itself and its subdomains, or `name.*` for the
+//!   registered domain `name` under any public suffix of a domain registry
+//!   (`amazon.de`, `amazon.co.uk`, but not `amazon.github.io`)
+//! - `category` - The category, or nothing to leave the domain uncategorized
+//!
+//! The most specific domain of the list wins, so `music.youtube.com` can be
+//! `music` while `youtube.com` is `video`. The version of the embedded list is
+//! returned by [`categories_version`].
+//!
+//! Lines starting with `#` are comments. More domains can be loaded from files
+//! in the same format, overriding the categories of the embedded list:
+//!
+//! - From the `URL_CATEGORIES_PATH` environment variable (several paths separated by `:`)
+//! - From command-line arguments of `url-category`, e.g.
+//!   `<command>url-category /etc/clickhouse-server/url_categories.csv</command>`
+
+use anyhow::{bail, Context, Result};
+use shared::table::GlobalTable;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::domain::host_of;
+use crate::idn::host_to_ascii;
+use crate::psl::icann_suffix_list;
+
+/// Environment variable listing extra category files to load.
+pub const CATEGORIES_PATH_ENV: &str = "URL_CATEGORIES_PATH";
+
+static CATEGORIES_DATA: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/",
+    "src/url_categories.csv"
+));
+
+static CATEGORIZER: GlobalTable<Categorizer> = GlobalTable::new(
+    "URL categorizer",
+    CATEGORIES_PATH_ENV,
+    Categorizer::embedded,
+    |categorizer, path| categorizer.load_file(path),
+);
+
+/// Returns the version of the embedded list, from its `# version:` comment.
+pub fn categories_version() -> &'static str {
+    CATEGORIES_DATA
+        .lines()
+        .find_map(|line| line.strip_prefix("# version:"))
+        .map(str::trim)
+        .unwrap_or_default()
+}
+
+/// The categories of domains, from the embedded list and the files loaded after it.
+#[derive(Debug, Clone, Default)]
+pub struct Categorizer {
+    /// Category of each ASCII domain or `name.*` pattern, empty when uncategorized.
+    categories: HashMap<String, String>,
+}
+
+impl Categorizer {
+    /// Creates a categorizer with the embedded `url_categories.csv` list.
+    pub fn embedded() -> Self {
+        let mut categorizer = Categorizer::default();
+        categorizer
+            .insert_lines(CATEGORIES_DATA)
+            .expect("Invalid embedded URL categories");
+        categorizer
+    }
+
+    /// Loads a category file, whose categories override the existing ones.
+    ///
+    /// # Returns
+    ///
+    /// The number of domains loaded from the file.
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<usize> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read URL categories: {}", path.display()))?;
+
+        let mut categorizer = self.clone();
+        let count = categorizer
+            .insert_lines(&content)
+            .with_context(|| format!("Failed to parse URL categories: {}", path.display()))?;
+        *self = categorizer;
+
+        Ok(count)
+    }
+
+    /// Adds `domain,category` lines, skipping comments, blank lines and the header.
+    fn insert_lines(&mut self, content: &str) -> Result<usize> {
+        let mut count = 0;
+
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line == "domain,category" {
+                continue;
+            }
+
+            let Some((domain, category)) = line.split_once(',') else {
+                bail!("Expected domain,category: {:?}", line);
+            };
+            let domain = domain.trim().trim_end_matches('.');
+            let domain = match domain.strip_suffix(".*") {
+                Some(name) => host_to_ascii(name).map(|name| format!("{}.*", name)),
+                None => host_to_ascii(domain),
+            };
+            let Some(domain) = domain.filter(|domain| !domain.is_empty()) else {
+                bail!("Invalid domain: {:?}", line);
+            };
+
+            self.categories.insert(domain, category.trim().to_string());
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Returns the category of a host name.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(&str)` - The category of the most specific listed domain, or an
+    ///   empty string when it is listed without a category
+    /// * `None` - If neither the host nor its parent domains are listed
+    pub fn category(&self, host: &str) -> Option<&str> {
+        let host = host_to_ascii(host.trim_end_matches('.'))?;
+        let registered_domain = icann_suffix_list().registered_domain(&host);
+
+        let suffixes = std::iter::once(0).chain(host.match_indices('.').map(|(i, _)| i + 1));
+        for start in suffixes {
+            let suffix = &host[start..];
+            if let Some(category) = self.categories.get(suffix) {
+                return Some(category);
+            }
+
+            if registered_domain.as_deref() == Some(suffix) {
+                let name = suffix.split('.').next().unwrap_or_default();
+                if let Some(category) = self.categories.get(&format!("{}.*", name)) {
+                    return Some(category);
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Initializes the global categorizer from `URL_CATEGORIES_PATH` and the given
+/// category files, before the first lookup. See [`GlobalTable::init`].
+pub fn init_categorizer<I, P>(paths: I) -> Result<()>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    CATEGORIZER.init(paths)
+}
+
+/// Returns the global categorizer, with the embedded categories unless set with
+/// [`init_categorizer`].
+fn categorizer() -> &'static Categorizer {
+    CATEGORIZER.get()
+}
+
+/// Returns the category of the URL in a text.
+///
+/// # Arguments
+///
+/// * `s` - A URL, a text containing a URL, or a host name
+///
+/// # Returns
+///
+/// * `Some(String)` - The category, or an empty string when the host is not
+///   categorized
+/// * `None` - If no host is found
+///
+/// # Examples
+///
+/// ```
+/// use url::category::url_category;
+///
+/// assert_eq!(url_category("https://www.youtube.com/watch?v=x").unwrap(), "video");
+/// assert_eq!(url_category("see https://bit.ly/3abc").unwrap(), "shortener");
+/// assert_eq!(url_category("https://www.amazon.co.uk/dp/B0").unwrap(), "ecommerce");
+/// assert_eq!(url_category("https://example.org/").unwrap(), "");
+/// assert!(url_category("not a url").is_none());
+/// ```
+pub fn url_category(s: &str) -> Option<String> {
+    let host = host_of(s)?;
+    Some(
+        categorizer()
+            .category(&host)
+            .unwrap_or_default()
+            .to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_categories() {
+        let categorizer = Categorizer::embedded();
+        for (host, expected) in [
+            ("facebook.com", Some("social")),
+            ("M.Facebook.com.", Some("social")),
+            ("www.google.com", Some("search")),
+            ("www.google.com.br", Some("search")),
+            ("mail.google.com", Some("webmail")),
+            ("music.youtube.com", Some("music")),
+            ("i.ytimg.com", Some("cdn")),
+            ("d111111abcdef8.cloudfront.net", Some("cdn")),
+            ("stats.g.doubleclick.net", Some("advertising")),
+            ("amazon.de", Some("ecommerce")),
+            ("amazonaws.com", Some("cloud")),
+            ("google.evil.com", None),
+            ("amazon.github.io", Some("cloud")),
+            ("google.blogspot.com", None),
+            ("notgoogle.com", None),
+            ("example.org", None),
+        ] {
+            assert_eq!(categorizer.category(host), expected, "{}", host);
+        }
+    }
+
+    #[test]
+    fn test_categories_version() {
+        assert!(!categories_version().is_empty());
+    }
+
+    #[test]
+    fn test_load_file() {
+        let path = std::env::temp_dir().join(format!("url-categories-{}.csv", std::process::id()));
+        std::fs::write(
+            &path,
+            "domain,category\nintranet.example.com,internal\nyoutube.com,entertainment\nbit.ly,\n",
+        )
+        .unwrap();
+
+        let mut categorizer = Categorizer::embedded();
+        assert_eq!(categorizer.load_file(&path).unwrap(), 3);
+        assert_eq!(
+            categorizer.category("wiki.intranet.example.com"),
+            Some("internal")
+        );
+        assert_eq!(
+            categorizer.category("www.youtube.com"),
+            Some("entertainment")
+        );
+        assert_eq!(categorizer.category("bit.ly"), Some(""));
+        assert_eq!(categorizer.category("music.youtube.com"), Some("music"));
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(categorizer
+            .load_file("/nonexistent/categories.csv")
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let mut categorizer = Categorizer::default();
+        assert!(categorizer.insert_lines("example.com").is_err());
+        assert!(categorizer.insert_lines("exa mple.com,x").is_err());
+        assert!(categorizer.insert_lines(",x").is_err());
+    }
+}
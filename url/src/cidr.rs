@@ -0,0 +1,350 @@
+//! Matching IP addresses against CIDR lists.
+//!
+//! A CIDR list is a text file with one range per line, such as private networks,
+//! the published ranges of a cloud provider or a blocklist:
+//!
+//! ```text
+//! # RFC 1918
+//! 10.0.0.0/8
+//! 172.16.0.0/12    private
+//! 192.168.0.0/16,private
+//! 2001:db8::/32
+//! 203.0.113.7
+//! ```
+//!
+//! An address without a prefix length is a single host. Anything after the range
+//! on a line, separated by whitespace or a comma, is ignored, as are blank lines
+//! and lines starting with `#`. The ranges are stored in a binary prefix trie, so
+//! a lookup takes at most 32 steps for IPv4 and 128 for IPv6, whatever the size
+//! of the list.
+//!
+//! The lists are loaded from local files:
+//!
+//! - From the `URL_CIDR_LIST_PATH` environment variable (several paths separated by `:`)
+//! - From command-line arguments of `ip-in-cidr-list`, e.g.
+//!   `<command>ip-in-cidr-list /etc/clickhouse-server/blocklist.txt</command>`
+//!
+//! Short lists can also be given as arguments directly:
+//! `<command>ip-in-cidr-list 10.0.0.0/8 172.16.0.0/12 192.168.0.0/16</command>`.
+//!
+//! Several functions with different lists can be declared to tag several kinds
+//! of ranges.
+
+use anyhow::{bail, Context, Result};
+use shared::table::GlobalTable;
+use std::net::IpAddr;
+use std::path::Path;
+
+use crate::ip::detect_ips;
+
+/// Environment variable listing the CIDR list files to load.
+pub const CIDR_LIST_PATH_ENV: &str = "URL_CIDR_LIST_PATH";
+
+static CIDR_LIST: GlobalTable<CidrList> = GlobalTable::new(
+    "CIDR list",
+    CIDR_LIST_PATH_ENV,
+    CidrList::new,
+    |list, path| list.load_file(path),
+);
+
+/// A binary trie of network prefixes, one bit per level.
+#[derive(Debug, Clone)]
+struct PrefixTrie {
+    /// Child nodes for bits 0 and 1, as indices in `nodes`, 0 for none.
+    nodes: Vec<[u32; 2]>,
+    /// Whether the prefix ending at each node is in the list.
+    terminal: Vec<bool>,
+}
+
+impl Default for PrefixTrie {
+    fn default() -> Self {
+        PrefixTrie {
+            nodes: vec![[0, 0]],
+            terminal: vec![false],
+        }
+    }
+}
+
+impl PrefixTrie {
+    /// Adds the first `len` bits of `bits`, most significant first.
+    fn insert(&mut self, bits: u128, len: u32) {
+        let mut node = 0;
+
+        for i in 0..len {
+            if self.terminal[node] {
+                // A shorter prefix already covers this one
+                return;
+            }
+
+            let bit = ((bits >> (127 - i)) & 1) as usize;
+            if self.nodes[node][bit] == 0 {
+                self.nodes[node][bit] = self.nodes.len() as u32;
+                self.nodes.push([0, 0]);
+                self.terminal.push(false);
+            }
+            node = self.nodes[node][bit] as usize;
+        }
+
+        self.terminal[node] = true;
+    }
+
+    /// Returns whether a prefix of the first `len` bits of `bits` is in the trie.
+    fn contains(&self, bits: u128, len: u32) -> bool {
+        let mut node = 0;
+
+        for i in 0..len {
+            if self.terminal[node] {
+                return true;
+            }
+
+            let bit = ((bits >> (127 - i)) & 1) as usize;
+            match self.nodes[node][bit] {
+                0 => return false,
+                child => node = child as usize,
+            }
+        }
+
+        self.terminal[node]
+    }
+}
+
+/// A list of IPv4 and IPv6 ranges.
+#[derive(Debug, Clone, Default)]
+pub struct CidrList {
+    ipv4: PrefixTrie,
+    ipv6: PrefixTrie,
+    len: usize,
+}
+
+impl CidrList {
+    /// Creates an empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a range in CIDR notation (`10.0.0.0/8`, `2001:db8::/32`) or a single address.
+    ///
+    /// Bits of the address after the prefix length are ignored.
+    pub fn insert(&mut self, cidr: &str) -> Result<()> {
+        let (addr, len) = match cidr.split_once('/') {
+            Some((addr, len)) => {
+                let len: u32 = len
+                    .parse()
+                    .with_context(|| format!("Invalid prefix length: {:?}", cidr))?;
+                (addr, Some(len))
+            }
+            None => (cidr, None),
+        };
+        let addr: IpAddr = addr
+            .parse()
+            .with_context(|| format!("Invalid IP address: {:?}", cidr))?;
+
+        let (trie, bits, max_len) = match addr {
+            IpAddr::V4(ip) => (&mut self.ipv4, (u32::from(ip) as u128) << 96, 32),
+            IpAddr::V6(ip) => (&mut self.ipv6, u128::from(ip), 128),
+        };
+        let len = len.unwrap_or(max_len);
+        if len > max_len {
+            bail!("Prefix length is larger than {}: {:?}", max_len, cidr);
+        }
+
+        trie.insert(bits, len);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Parses a list with one range per line, see the [module documentation](self).
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut list = CidrList::new();
+        list.insert_lines(content)?;
+        Ok(list)
+    }
+
+    /// Adds the ranges of a list file.
+    ///
+    /// # Returns
+    ///
+    /// The number of ranges loaded from the file.
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<usize> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read CIDR list: {}", path.display()))?;
+
+        let mut list = self.clone();
+        list.insert_lines(&content)
+            .with_context(|| format!("Failed to parse CIDR list: {}", path.display()))?;
+
+        let count = list.len - self.len;
+        *self = list;
+
+        Ok(count)
+    }
+
+    /// Adds the ranges of the lines of a list.
+    fn insert_lines(&mut self, content: &str) -> Result<()> {
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let cidr = line
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .next()
+                .unwrap_or_default();
+            self.insert(cidr)
+                .with_context(|| format!("Line {}", number + 1))?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of ranges added to the list.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the list has no ranges.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns whether an address is in one of the ranges.
+    ///
+    /// IPv4-mapped IPv6 addresses (`::ffff:10.0.0.1`) are looked up as IPv4 addresses.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match ip {
+            IpAddr::V4(ip) => self.ipv4.contains((u32::from(ip) as u128) << 96, 32),
+            IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+                Some(ip) => self.contains(IpAddr::V4(ip)),
+                None => self.ipv6.contains(u128::from(ip), 128),
+            },
+        }
+    }
+}
+
+/// Initializes the global CIDR list from `URL_CIDR_LIST_PATH` and the given arguments.
+///
+/// Each argument is either a range (`10.0.0.0/8`) or the path of a list file.
+/// This must be called before the first lookup, see [`GlobalTable::init`].
+///
+/// # Errors
+///
+/// Fails if a file cannot be loaded, or if no ranges were loaded at all.
+pub fn init_cidr_list(args: &[String]) -> Result<()> {
+    let mut list = CIDR_LIST.load_from_env()?;
+    for arg in args {
+        if list.insert(arg).is_err() {
+            list.load_file(arg)?;
+        }
+    }
+
+    if list.is_empty() {
+        bail!(
+            "No CIDR list: pass ranges or list files as arguments, or set {}",
+            CIDR_LIST_PATH_ENV
+        );
+    }
+
+    CIDR_LIST.set(list)
+}
+
+/// Returns the global CIDR list, loaded from the files in `URL_CIDR_LIST_PATH`
+/// unless set with [`init_cidr_list`].
+fn cidr_list() -> &'static CidrList {
+    CIDR_LIST.get()
+}
+
+/// Checks whether an IP address of a text is in the global CIDR list.
+///
+/// # Arguments
+///
+/// * `s` - The input string to search for IP addresses (see [`crate::ip`])
+///
+/// # Returns
+///
+/// * `Some("true")` - If one of the addresses of the text is in a listed range
+/// * `Some("false")` - If none is, or the text has no IP address
+///
+/// Note: This function always returns `Some`, never `None`.
+pub fn ip_in_cidr_list(s: &str) -> Option<String> {
+    let list = cidr_list();
+    let found = detect_ips(s)
+        .into_iter()
+        .any(|(_, _, ip)| list.contains(ip));
+    Some(found.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_contains() {
+        let list = CidrList::parse(
+            "# private\n10.0.0.0/8\n172.16.0.0/12 private\n192.168.1.77,host\n\n2001:db8::/32\n",
+        )
+        .unwrap();
+        assert_eq!(list.len(), 4);
+
+        for (addr, expected) in [
+            ("10.255.0.1", true),
+            ("11.0.0.1", false),
+            ("172.31.255.255", true),
+            ("172.32.0.0", false),
+            ("192.168.1.77", true),
+            ("192.168.1.78", false),
+            ("2001:db8:1::1", true),
+            ("2001:db9::1", false),
+            ("::ffff:10.1.2.3", true),
+            ("::a01:203", false),
+        ] {
+            assert_eq!(list.contains(ip(addr)), expected, "{}", addr);
+        }
+    }
+
+    #[test]
+    fn test_overlapping_prefixes() {
+        let mut list = CidrList::new();
+        list.insert("10.1.2.0/24").unwrap();
+        list.insert("10.0.0.0/8").unwrap();
+        list.insert("10.1.2.3/32").unwrap();
+        assert!(list.contains(ip("10.9.9.9")));
+
+        let mut all = CidrList::new();
+        all.insert("0.0.0.0/0").unwrap();
+        all.insert("::/0").unwrap();
+        assert!(all.contains(ip("203.0.113.1")));
+        assert!(all.contains(ip("2001:db8::1")));
+    }
+
+    #[test]
+    fn test_insert_errors() {
+        let mut list = CidrList::new();
+        assert!(list.insert("10.0.0.0/33").is_err());
+        assert!(list.insert("2001:db8::/129").is_err());
+        assert!(list.insert("10.0.0/8").is_err());
+        assert!(list.insert("10.0.0.0/x").is_err());
+        assert!(CidrList::parse("10.0.0.0/8\nnot-a-range\n").is_err());
+    }
+
+    #[test]
+    fn test_load_file() {
+        let path = std::env::temp_dir().join(format!("url-cidr-{}.txt", std::process::id()));
+        std::fs::write(&path, "198.51.100.0/24\n").unwrap();
+
+        let mut list = CidrList::parse("10.0.0.0/8").unwrap();
+        assert_eq!(list.load_file(&path).unwrap(), 1);
+        assert!(list.contains(ip("198.51.100.9")));
+        assert!(list.contains(ip("10.0.0.1")));
+        assert_eq!(list.len(), 2);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(list.load_file(&path).is_err());
+    }
+}
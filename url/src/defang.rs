@@ -0,0 +1,406 @@
+//! Defanged URLs, domains and IP addresses.
+//!
+//! Threat-intelligence reports and security logs write indicators in a
+//! "defanged" form so that they cannot be clicked or resolved by accident:
+//!
+//! ```text
+//! hxxps://evil[.]com/payload   evil(.)com   1.2.3[.]4   user[at]evil[dot]com
+//! ```
+//!
+//! [`url_refang`] restores the original text, so that it can be passed to the
+//! other functions of this crate, and [`url_defang`] does the opposite for the
+//! URLs, domains and IPv4 addresses of a text. [`has_indicator`] recognizes
+//! both forms.
+//!
+//! # Defanged Forms
+//!
+//! - `hxxp`, `hxxps`, `hxtp`, `hxtps`, `fxp` and `fxps` before `://` are schemes,
+//!   in any case, and are restored in lowercase
+//! - `[.]`, `(.)`, `{.}`, `[dot]` and `\.` are dots
+//! - `[:]`, `[://]` and `[/]` are the same characters without brackets
+//! - `[@]` and `[at]` are at signs
+//!
+//! Parentheses and braces are accepted in place of the square brackets, with
+//! spaces inside them (`[ . ]`) and in any case (`[DOT]`).
+//!
+//! Indicators are often written without a scheme, so bare domains (`evil[.]com`)
+//! are always recognized here, even when they are off for the other functions
+//! (see [`crate::url`]).
+//!
+//! # Examples
+//!
+//! ```
+//! use url::defang::{has_indicator, url_defang, url_refang};
+//!
+//! assert_eq!(url_refang("hxxps://evil[.]com/a").unwrap(), "https://evil.com/a");
+//! assert_eq!(url_defang("https://evil.com/a").unwrap(), "hxxps://evil[.]com/a");
+//! assert_eq!(has_indicator("seen at 1.2.3[.]4").unwrap(), "true");
+//! ```
+
+use std::sync::OnceLock;
+
+use crate::ip::ipv4_spans;
+use crate::url::{url_detector, UrlDetector};
+
+static INDICATOR_DETECTOR: OnceLock<UrlDetector> = OnceLock::new();
+
+/// Defanged schemes and the schemes they stand for, longest first
+const DEFANGED_SCHEMES: &[(&str, &str)] = &[
+    ("hxxps", "https"),
+    ("hxtps", "https"),
+    ("hxxp", "http"),
+    ("hxtp", "http"),
+    ("fxps", "ftps"),
+    ("fxp", "ftp"),
+];
+
+/// Schemes that are defanged by [`url_defang`], with their defanged form
+const FANGED_SCHEMES: &[(&str, &str)] = &[
+    ("https", "hxxps"),
+    ("http", "hxxp"),
+    ("ftps", "fxps"),
+    ("ftp", "fxp"),
+];
+
+/// Restores the defanged URLs, domains and IP addresses of a text.
+///
+/// # Arguments
+///
+/// * `s` - The input string, with or without defanged indicators
+///
+/// # Returns
+///
+/// * `Some(String)` - The text with the defanged forms replaced by the original
+///   characters; other text is unchanged
+///
+/// Note: This function always returns `Some`, never `None`.
+///
+/// # Examples
+///
+/// ```
+/// use url::defang::url_refang;
+///
+/// assert_eq!(
+///     url_refang("C2 at hXXp[:]//bad(.)example{.}org/x").unwrap(),
+///     "C2 at http://bad.example.org/x"
+/// );
+/// assert_eq!(url_refang("1.2.3[.]4").unwrap(), "1.2.3.4");
+/// assert_eq!(url_refang("john[at]example[dot]com").unwrap(), "john@example.com");
+/// assert_eq!(url_refang("array[0] and f(x)").unwrap(), "array[0] and f(x)");
+/// ```
+pub fn url_refang(s: &str) -> Option<String> {
+    Some(refang_schemes(&refang_brackets(s)))
+}
+
+/// Replaces the bracketed separators (`[.]`, `(dot)`, `[://]`, ...) and `\.`.
+fn refang_brackets(s: &str) -> String {
+    let mut refanged = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(i) = rest.find(['[', '(', '{', '\\']) {
+        refanged.push_str(&rest[..i]);
+        rest = &rest[i..];
+
+        if let Some(after) = rest.strip_prefix("\\.") {
+            refanged.push('.');
+            rest = after;
+        } else if let Some((separator, len)) = bracketed_separator(rest) {
+            refanged.push_str(separator);
+            rest = &rest[len..];
+        } else {
+            let len = rest.chars().next().map_or(1, char::len_utf8);
+            refanged.push_str(&rest[..len]);
+            rest = &rest[len..];
+        }
+    }
+
+    refanged.push_str(rest);
+    refanged
+}
+
+/// Returns the separator of a bracketed token at the start of `s`, with the
+/// length of the token.
+fn bracketed_separator(s: &str) -> Option<(&'static str, usize)> {
+    let close = match s.as_bytes().first()? {
+        b'[' => ']',
+        b'(' => ')',
+        b'{' => '}',
+        _ => return None,
+    };
+
+    // The longest token is `[ :// ]` with a few spaces
+    let end = s[1..]
+        .char_indices()
+        .take(8)
+        .find(|&(_, c)| c == close)
+        .map(|(i, _)| i + 1)?;
+    let separator = match s[1..end].trim().to_ascii_lowercase().as_str() {
+        "." | "dot" => ".",
+        ":" => ":",
+        "://" => "://",
+        "/" => "/",
+        "@" | "at" => "@",
+        _ => return None,
+    };
+
+    Some((separator, end + 1))
+}
+
+/// Replaces the defanged schemes that start a word and are followed by `://`.
+fn refang_schemes(s: &str) -> String {
+    let mut refanged = String::with_capacity(s.len());
+    let mut last = 0;
+
+    for (i, _) in s.char_indices() {
+        if i < last
+            || s[..i]
+                .chars()
+                .next_back()
+                .is_some_and(char::is_alphanumeric)
+        {
+            continue;
+        }
+
+        let rest = &s[i..];
+        let scheme = DEFANGED_SCHEMES.iter().find(|(defanged, _)| {
+            rest.get(..defanged.len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case(defanged))
+                && rest[defanged.len()..].starts_with("://")
+        });
+
+        if let Some((defanged, scheme)) = scheme {
+            refanged.push_str(&s[last..i]);
+            refanged.push_str(scheme);
+            last = i + defanged.len();
+        }
+    }
+
+    refanged.push_str(&s[last..]);
+    refanged
+}
+
+/// Defangs the URLs, domains and IPv4 addresses of a text.
+///
+/// The `http`, `https`, `ftp` and `ftps` schemes become `hxxp`, `hxxps`, `fxp`
+/// and `fxps`, and the dots of host names, email domains and IPv4 addresses
+/// become `[.]`. Paths and queries are left as they are, as are indicators that
+/// are already defanged.
+///
+/// # Arguments
+///
+/// * `s` - The input string to defang
+///
+/// # Returns
+///
+/// * `Some(String)` - The defanged text
+///
+/// Note: This function always returns `Some`, never `None`.
+///
+/// # Examples
+///
+/// ```
+/// use url::defang::url_defang;
+///
+/// assert_eq!(
+///     url_defang("see https://evil.com/a.php and 1.2.3.4").unwrap(),
+///     "see hxxps://evil[.]com/a.php and 1[.]2[.]3[.]4"
+/// );
+/// assert_eq!(
+///     url_defang("mailto:john@mail.evil.com").unwrap(),
+///     "mailto:john@mail[.]evil[.]com"
+/// );
+/// assert_eq!(url_defang("hxxps://evil[.]com").unwrap(), "hxxps://evil[.]com");
+/// ```
+pub fn url_defang(s: &str) -> Option<String> {
+    let mut defanged = String::with_capacity(s.len() + 16);
+    let mut last = 0;
+
+    for (start, end) in indicator_detector().detect_all(s) {
+        defang_ipv4s(&s[last..start], &mut defanged);
+        defang_url(&s[start..end], &mut defanged);
+        last = end;
+    }
+    defang_ipv4s(&s[last..], &mut defanged);
+
+    Some(defanged)
+}
+
+/// Appends a defanged URL.
+fn defang_url(url: &str, defanged: &mut String) {
+    let (host_start, host_end) = host_span(url);
+
+    let mut rest = &url[..host_start];
+    for (scheme, replacement) in FANGED_SCHEMES {
+        if let Some(after) = rest
+            .get(..scheme.len())
+            .filter(|prefix| prefix.eq_ignore_ascii_case(scheme))
+            .and_then(|_| rest[scheme.len()..].strip_prefix("://"))
+        {
+            defanged.push_str(replacement);
+            defanged.push_str("://");
+            rest = after;
+            break;
+        }
+    }
+
+    defanged.push_str(rest);
+    defanged.push_str(&url[host_start..host_end].replace('.', "[.]"));
+    defanged.push_str(&url[host_end..]);
+}
+
+/// Returns the byte range of the host of a detected URL, empty when it has none.
+fn host_span(url: &str) -> (usize, usize) {
+    let scheme_end = url
+        .find(':')
+        .filter(|&i| !url[..i].contains('.'))
+        .filter(|&i| {
+            url[..i]
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "+-".contains(c))
+        });
+
+    let Some(scheme_end) = scheme_end else {
+        // Bare domain, possibly with a port
+        let end = url.find([':', '/', '?', '#']).unwrap_or(url.len());
+        return (0, end);
+    };
+
+    if let Some(authority) = url[scheme_end..].strip_prefix("://") {
+        let start = url.len() - authority.len();
+        let authority_end = start + authority.find(['/', '?', '#']).unwrap_or(authority.len());
+        let host_start = url[start..authority_end]
+            .rfind('@')
+            .map_or(start, |at| start + at + 1);
+        let host_end = if url[host_start..].starts_with('[') {
+            host_start
+        } else {
+            host_start
+                + url[host_start..authority_end]
+                    .find(':')
+                    .unwrap_or(authority_end - host_start)
+        };
+        (host_start, host_end)
+    } else if url[..scheme_end].eq_ignore_ascii_case("mailto") {
+        let end = url.find('?').unwrap_or(url.len());
+        let start = url[..end].rfind('@').map_or(end, |at| at + 1);
+        (start, end)
+    } else {
+        (url.len(), url.len())
+    }
+}
+
+/// Appends a text with the dots of its IPv4 addresses defanged.
+fn defang_ipv4s(s: &str, defanged: &mut String) {
+    let mut last = 0;
+
+    for (start, end) in ipv4_spans(s) {
+        defanged.push_str(&s[last..start]);
+        defanged.push_str(&s[start..end].replace('.', "[.]"));
+        last = end;
+    }
+
+    defanged.push_str(&s[last..]);
+}
+
+/// Checks whether a text contains a URL, a domain or an IPv4 address, defanged or not.
+///
+/// # Arguments
+///
+/// * `s` - The input string to check for indicators
+///
+/// # Returns
+///
+/// * `Some("true")` - If the string contains an indicator
+/// * `Some("false")` - If it does not
+///
+/// Note: This function always returns `Some`, never `None`.
+///
+/// # Examples
+///
+/// ```
+/// use url::defang::has_indicator;
+///
+/// assert_eq!(has_indicator("beacon to hxxps://evil[.]com").unwrap(), "true");
+/// assert_eq!(has_indicator("blocked 10.0.0[.]1").unwrap(), "true");
+/// assert_eq!(has_indicator("version 1.2.3").unwrap(), "false");
+/// ```
+pub fn has_indicator(s: &str) -> Option<String> {
+    let refanged = refang_schemes(&refang_brackets(s));
+    let found = indicator_detector().detect_from(&refanged, 0).is_some()
+        || !ipv4_spans(&refanged).is_empty();
+    Some(found.to_string())
+}
+
+/// Returns the global URL detector, with bare domains.
+fn indicator_detector() -> &'static UrlDetector {
+    INDICATOR_DETECTOR.get_or_init(|| url_detector().clone().with_bare_domains(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refang() {
+        for (defanged, expected) in [
+            ("hxxps://evil[.]com/path", "https://evil.com/path"),
+            ("HXXP://evil[.]com", "http://evil.com"),
+            ("hxxp[://]evil[.]com", "http://evil.com"),
+            ("fxp://files(.)evil{.}com", "ftp://files.evil.com"),
+            ("evil [ . ] com", "evil . com"),
+            ("evil[ . ]com", "evil.com"),
+            ("evil[DOT]com", "evil.com"),
+            ("evil\\.com", "evil.com"),
+            ("http[:]//1.2.3[.]4[/]x", "http://1.2.3.4/x"),
+            ("john[@]evil[.]com", "john@evil.com"),
+            ("myhxxp://x", "myhxxp://x"),
+            ("hxxp is a typo", "hxxp is a typo"),
+            ("see [link](x) {a} (b", "see [link](x) {a} (b"),
+            ("städte[.]de", "städte.de"),
+            ("see (日本) here", "see (日本) here"),
+            ("[é] and {ü}", "[é] and {ü}"),
+            ("(日本語のテキスト) [.]", "(日本語のテキスト) ."),
+        ] {
+            assert_eq!(url_refang(defanged).unwrap(), expected, "{}", defanged);
+        }
+    }
+
+    #[test]
+    fn test_defang() {
+        for (text, expected) in [
+            (
+                "https://user@www.evil.com:8443/a.b?c=d.e",
+                "hxxps://user@www[.]evil[.]com:8443/a.b?c=d.e",
+            ),
+            ("go to evil.co.uk/x.", "go to evil[.]co[.]uk/x."),
+            ("ftp://10.0.0.1/f", "fxp://10[.]0[.]0[.]1/f"),
+            ("from 192.168.1.10:22.", "from 192[.]168[.]1[.]10:22."),
+            ("http://[::1]:80/", "hxxp://[::1]:80/"),
+            ("ssh://git.evil.com", "ssh://git[.]evil[.]com"),
+            (
+                "version 1.2.3.4.5 and 999.1.1.1",
+                "version 1.2.3.4.5 and 999.1.1.1",
+            ),
+            ("no indicators here", "no indicators here"),
+        ] {
+            assert_eq!(url_defang(text).unwrap(), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_defang_refang_roundtrip() {
+        let text = "callback https://cdn.evil.com/x.js from 203.0.113.7";
+        let defanged = url_defang(text).unwrap();
+        assert_eq!(url_defang(&defanged).unwrap(), defanged);
+        assert_eq!(url_refang(&defanged).unwrap(), text);
+    }
+
+    #[test]
+    fn test_has_indicator() {
+        assert_eq!(has_indicator("hxxp://evil(.)com").unwrap(), "true");
+        assert_eq!(has_indicator("evil[.]com").unwrap(), "true");
+        assert_eq!(has_indicator("https://example.org").unwrap(), "true");
+        assert_eq!(has_indicator("nothing to see").unwrap(), "false");
+        assert_eq!(has_indicator("tags {ü}").unwrap(), "false");
+    }
+}
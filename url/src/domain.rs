@@ -0,0 +1,162 @@
+//! Registered domain, public suffix and subdomain of URLs.
+//!
+//! These functions split the host of a URL with the Public Suffix List (see
+//! [`crate::psl`]), unlike `cutToFirstSignificantSubdomain` and its small
+//! built-in list of top-level domains:
+//!
+//! ```text
+//! https://blog.alice.github.io/post
+//!         └┬─┘ └─────┬───────┘
+//!  subdomain   registered domain
+//!                    └───┬───┘
+//!                  public suffix
+//! ```
+//!
+//! The input is a URL, a text containing one, or a host name.
+//!
+//! # Examples
+//!
+//! ```
+//! use url::domain::{url_public_suffix, url_registered_domain, url_subdomain};
+//!
+//! let url = "https://blog.alice.github.io/post";
+//! assert_eq!(url_registered_domain(url).unwrap(), "alice.github.io");
+//! assert_eq!(url_public_suffix(url).unwrap(), "github.io");
+//! assert_eq!(url_subdomain(url).unwrap(), "blog");
+//! ```
+
+use crate::parts::url_parts;
+use crate::psl::public_suffix_list;
+
+/// Returns the host of the URL in a text, or the text itself when it is a host name.
+pub(crate) fn host_of(s: &str) -> Option<String> {
+    if let Some(host) = url_parts(s).map(|parts| parts.host) {
+        return (!host.is_empty()).then_some(host);
+    }
+
+    let s = s.trim();
+    let is_host = !s.is_empty()
+        && !s.contains(|c: char| c.is_whitespace() || matches!(c, '/' | ':' | '@' | '?' | '#'));
+    is_host.then(|| s.to_string())
+}
+
+/// Returns the registered domain (eTLD+1) of a URL.
+///
+/// # Arguments
+///
+/// * `s` - A URL, a text containing a URL, or a host name
+///
+/// # Returns
+///
+/// * `Some(String)` - The registered domain, lowercased, or an empty string when
+///   the host is an IP address or a public suffix
+/// * `None` - If no host is found
+///
+/// # Examples
+///
+/// ```
+/// use url::domain::url_registered_domain;
+///
+/// assert_eq!(
+///     url_registered_domain("https://shop.example.co.uk/cart").unwrap(),
+///     "example.co.uk"
+/// );
+/// assert_eq!(
+///     url_registered_domain("news.blogspot.co.uk").unwrap(),
+///     "news.blogspot.co.uk"
+/// );
+/// assert_eq!(url_registered_domain("http://127.0.0.1/").unwrap(), "");
+/// assert!(url_registered_domain("not a url").is_none());
+/// ```
+pub fn url_registered_domain(s: &str) -> Option<String> {
+    let host = host_of(s)?;
+    Some(
+        public_suffix_list()
+            .registered_domain(&host)
+            .unwrap_or_default(),
+    )
+}
+
+/// Returns the public suffix (eTLD) of a URL.
+///
+/// # Arguments
+///
+/// * `s` - A URL, a text containing a URL, or a host name
+///
+/// # Returns
+///
+/// * `Some(String)` - The public suffix, lowercased, or an empty string when the
+///   host is an IP address
+/// * `None` - If no host is found
+///
+/// # Examples
+///
+/// ```
+/// use url::domain::url_public_suffix;
+///
+/// assert_eq!(url_public_suffix("https://a.b.example.com.au/").unwrap(), "com.au");
+/// assert_eq!(url_public_suffix("alice.github.io").unwrap(), "github.io");
+/// ```
+pub fn url_public_suffix(s: &str) -> Option<String> {
+    let host = host_of(s)?;
+    Some(
+        public_suffix_list()
+            .public_suffix(&host)
+            .unwrap_or_default(),
+    )
+}
+
+/// Returns the labels of the host of a URL before its registered domain.
+///
+/// # Arguments
+///
+/// * `s` - A URL, a text containing a URL, or a host name
+///
+/// # Returns
+///
+/// * `Some(String)` - The subdomain, lowercased, or an empty string when there
+///   is none
+/// * `None` - If no host is found
+///
+/// # Examples
+///
+/// ```
+/// use url::domain::url_subdomain;
+///
+/// assert_eq!(url_subdomain("https://a.b.example.co.uk/").unwrap(), "a.b");
+/// assert_eq!(url_subdomain("https://example.co.uk/").unwrap(), "");
+/// ```
+pub fn url_subdomain(s: &str) -> Option<String> {
+    let host = host_of(s)?;
+    Some(public_suffix_list().subdomain(&host).unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(
+            host_of("see https://User@Example.org:8080/x").unwrap(),
+            "example.org"
+        );
+        assert_eq!(host_of(" münchen.de ").unwrap(), "münchen.de");
+        assert_eq!(host_of("localhost").unwrap(), "localhost");
+        assert!(host_of("file:///etc/hosts").is_none());
+        assert!(host_of("two words").is_none());
+        assert!(host_of("").is_none());
+    }
+
+    #[test]
+    fn test_url_domains() {
+        let url = "https://www.食狮.公司.cn/path";
+        assert_eq!(url_registered_domain(url).unwrap(), "食狮.公司.cn");
+        assert_eq!(url_public_suffix(url).unwrap(), "公司.cn");
+        assert_eq!(url_subdomain(url).unwrap(), "www");
+
+        assert_eq!(url_registered_domain("localhost").unwrap(), "");
+        assert_eq!(url_public_suffix("http://[::1]:8080/").unwrap(), "");
+        assert_eq!(url_subdomain("github.io").unwrap(), "");
+    }
+}
@@ -0,0 +1,418 @@
+//! Internationalized domain names (IDN).
+//!
+//! Hosts can be written in Unicode (`münchen.de`) or in their ASCII form with
+//! Punycode labels (`xn--mnchen-3ya.de`). [`url_to_ascii`] and [`url_to_unicode`]
+//! convert the host of a URL between both forms with the UTS #46 processing of
+//! browsers, which also maps case and compatibility characters (`Ｍünchen.de`),
+//! so that URLs can be compared.
+//!
+//! # Homographs
+//!
+//! Letters of other scripts can look like Latin ones, as the Cyrillic `а` and
+//! `р` of `аррӏе.com`. [`is_homograph`] flags hosts with such lookalikes,
+//! following the spirit of the IDN display policies of browsers:
+//!
+//! - A label mixing scripts, apart from Latin with the scripts of Chinese,
+//!   Japanese and Korean
+//! - A label written only with Cyrillic or Greek letters that look like Latin
+//!   ones, outside the top-level domains of countries using these scripts
+//! - A label that cannot be decoded
+//!
+//! # Examples
+//!
+//! ```
+//! use url::idn::{is_homograph, url_to_ascii, url_to_unicode};
+//!
+//! assert_eq!(
+//!     url_to_ascii("https://München.de/karte").unwrap(),
+//!     "https://xn--mnchen-3ya.de/karte"
+//! );
+//! assert_eq!(
+//!     url_to_unicode("https://xn--mnchen-3ya.de/karte").unwrap(),
+//!     "https://münchen.de/karte"
+//! );
+//! assert!(is_homograph("www.аррӏе.com"));
+//! assert!(!is_homograph("www.münchen.de"));
+//! ```
+
+use std::net::IpAddr;
+
+use idna::uts46::{AsciiDenyList, Hyphens, Uts46};
+
+use crate::domain::host_of;
+use crate::parts::url_parts;
+use crate::url::detect_url;
+
+/// Cyrillic letters that look like Latin letters
+const CYRILLIC_LOOKALIKES: &str = "аԁеһіјӏорԛѕсԝхуъыьҽпгѵѡ";
+
+/// Greek letters that look like Latin letters
+const GREEK_LOOKALIKES: &str = "αικνορτυχ";
+
+/// Top-level domains of countries using the Cyrillic script
+const CYRILLIC_TLDS: &[&str] = &[
+    "by", "bg", "kg", "kz", "mk", "mn", "rs", "ru", "su", "tj", "ua", "uz", "бг", "бел", "мкд",
+    "мон", "рф", "срб", "укр", "қаз",
+];
+
+/// Top-level domains of countries using the Greek script
+const GREEK_TLDS: &[&str] = &["cy", "gr", "ελ"];
+
+/// Writing systems of domain labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Thai,
+    Georgian,
+    Hangul,
+    Hiragana,
+    Katakana,
+    Bopomofo,
+    Han,
+    Other,
+}
+
+/// Returns the script of a character, or `None` for digits, hyphens and marks
+/// shared by all scripts.
+fn script_of(c: char) -> Option<Script> {
+    let script = match c as u32 {
+        0x41..=0x5A | 0x61..=0x7A => Script::Latin,
+        0x00..=0x7F => return None,
+        0xD7 | 0xF7 => return None,
+        0xC0..=0x24F | 0x250..=0x2AF | 0x1E00..=0x1EFF | 0x2C60..=0x2C7F | 0xA720..=0xA7FF => {
+            Script::Latin
+        }
+        0x300..=0x36F | 0x200C | 0x200D => return None,
+        0x370..=0x3FF | 0x1F00..=0x1FFF => Script::Greek,
+        0x400..=0x52F | 0x1C80..=0x1C8F | 0x2DE0..=0x2DFF | 0xA640..=0xA69F => Script::Cyrillic,
+        0x530..=0x58F => Script::Armenian,
+        0x590..=0x5FF => Script::Hebrew,
+        0x600..=0x6FF | 0x750..=0x77F | 0x8A0..=0x8FF => Script::Arabic,
+        0x900..=0x97F => Script::Devanagari,
+        0xE00..=0xE7F => Script::Thai,
+        0x10A0..=0x10FF => Script::Georgian,
+        0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Script::Hangul,
+        0x3040..=0x309F => Script::Hiragana,
+        0x30A0..=0x30FF | 0x31F0..=0x31FF => Script::Katakana,
+        0x3100..=0x312F => Script::Bopomofo,
+        0x2E80..=0x2FDF | 0x3005 | 0x3007 | 0x3021..=0x3029 | 0x3400..=0x4DBF => Script::Han,
+        0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x3FFFF => Script::Han,
+        _ => Script::Other,
+    };
+
+    Some(script)
+}
+
+/// Returns whether the scripts of a label may be mixed, as in Japanese text
+/// mixing Han, Hiragana, Katakana and Latin letters.
+fn is_allowed_mix(scripts: &[Script]) -> bool {
+    const CJK_MIXES: &[&[Script]] = &[
+        &[
+            Script::Latin,
+            Script::Han,
+            Script::Hiragana,
+            Script::Katakana,
+        ],
+        &[Script::Latin, Script::Han, Script::Bopomofo],
+        &[Script::Latin, Script::Han, Script::Hangul],
+    ];
+
+    scripts.len() <= 1
+        || CJK_MIXES
+            .iter()
+            .any(|mix| scripts.iter().all(|script| mix.contains(script)))
+}
+
+/// Converts a host name to its ASCII form, with Punycode for non-ASCII labels.
+///
+/// # Returns
+///
+/// * `Some(String)` - The lowercased ASCII host
+/// * `None` - If the host is not a valid domain name under UTS #46, or has
+///   characters forbidden in URL hosts (` `, `%`, `<`, ...)
+pub fn host_to_ascii(host: &str) -> Option<String> {
+    idna::domain_to_ascii_cow(host.as_bytes(), AsciiDenyList::URL)
+        .ok()
+        .map(|host| host.into_owned())
+}
+
+/// Converts a host name to its Unicode form, decoding Punycode labels.
+///
+/// # Returns
+///
+/// * `Some(String)` - The lowercased Unicode host
+/// * `None` - If the host is not a valid domain name under UTS #46, or has
+///   characters forbidden in URL hosts (` `, `%`, `<`, ...)
+pub fn host_to_unicode(host: &str) -> Option<String> {
+    let (unicode, result) =
+        Uts46::new().to_unicode(host.as_bytes(), AsciiDenyList::URL, Hyphens::Allow);
+    result.ok().map(|_| unicode.into_owned())
+}
+
+/// Returns whether a host name contains lookalikes of Latin letters.
+///
+/// See the [module documentation](self) for the rules. IP addresses are not
+/// homographs. Other hosts that are not valid domain names are flagged, as they
+/// cannot be displayed safely.
+///
+/// # Examples
+///
+/// ```
+/// use url::idn::is_homograph;
+///
+/// // Cyrillic "а" in a Latin label
+/// assert!(is_homograph("pаypal.com"));
+/// // The same, in Punycode
+/// assert!(is_homograph("xn--pypal-4ve.com"));
+/// // Only Cyrillic lookalikes under .com
+/// assert!(is_homograph("xn--80ak6aa92e.com"));
+///
+/// assert!(!is_homograph("paypal.com"));
+/// assert!(!is_homograph("пример.рф"));
+/// assert!(!is_homograph("食狮.公司.cn"));
+/// assert!(!is_homograph("[2001:db8::1]"));
+/// ```
+pub fn is_homograph(host: &str) -> bool {
+    let address = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+    if address.parse::<IpAddr>().is_ok() {
+        return false;
+    }
+
+    let Some(host) = host_to_unicode(host) else {
+        return true;
+    };
+    let labels: Vec<&str> = host.split('.').collect();
+    let tld = labels[labels.len() - 1];
+
+    labels.iter().any(|label| {
+        let scripts = scripts_of(label);
+        if !is_allowed_mix(&scripts) {
+            return true;
+        }
+
+        let (lookalikes, tlds) = match scripts.as_slice() {
+            [Script::Cyrillic] => (CYRILLIC_LOOKALIKES, CYRILLIC_TLDS),
+            [Script::Greek] => (GREEK_LOOKALIKES, GREEK_TLDS),
+            _ => return false,
+        };
+        label
+            .chars()
+            .all(|c| script_of(c).is_none() || lookalikes.contains(c))
+            && !tlds.contains(&tld)
+    })
+}
+
+/// Returns the distinct scripts of a label, in order of appearance.
+fn scripts_of(label: &str) -> Vec<Script> {
+    let mut scripts = Vec::new();
+    for script in label.chars().filter_map(script_of) {
+        if !scripts.contains(&script) {
+            scripts.push(script);
+        }
+    }
+    scripts
+}
+
+/// Returns the URL in a text, or the text itself when it is a host name, with
+/// the byte range of its host.
+fn url_with_host(s: &str) -> Option<(&str, usize, usize)> {
+    let url = match detect_url(s) {
+        Some((start, end)) => &s[start..end],
+        None => {
+            host_of(s)?;
+            s.trim()
+        }
+    };
+
+    let authority_start = match url.find("://") {
+        Some(i) => i + 3,
+        None => 0,
+    };
+    let authority_len = url[authority_start..]
+        .find(['/', '?', '#'])
+        .unwrap_or(url.len() - authority_start);
+    let authority = &url[authority_start..authority_start + authority_len];
+
+    let host_start = authority.rfind('@').map_or(0, |i| i + 1);
+    let host_len = match authority[host_start..].rfind(':') {
+        Some(_) if authority[host_start..].starts_with('[') => return None,
+        Some(i) => i,
+        None => authority.len() - host_start,
+    };
+    let start = authority_start + host_start;
+
+    (host_len > 0).then_some((url, start, start + host_len))
+}
+
+/// Converts the host of a URL with `convert`, keeping the rest of the URL.
+fn convert_host(s: &str, convert: fn(&str) -> Option<String>) -> Option<String> {
+    let (url, start, end) = url_with_host(s)?;
+    let host = convert(&url[start..end])?;
+
+    Some(format!("{}{}{}", &url[..start], host, &url[end..]))
+}
+
+/// Finds the URL in a text and converts its host to ASCII.
+///
+/// # Arguments
+///
+/// * `s` - A URL, a text containing a URL, or a host name
+///
+/// # Returns
+///
+/// * `Some(String)` - The URL, with its host lowercased and Punycode-encoded
+/// * `None` - If no host is found, or it is not a valid domain name
+///
+/// # Examples
+///
+/// ```
+/// use url::idn::url_to_ascii;
+///
+/// assert_eq!(url_to_ascii("食狮.公司.cn").unwrap(), "xn--85x722f.xn--55qx5d.cn");
+/// assert_eq!(
+///     url_to_ascii("http://user@Bücher.example:8080/ä?q=ü").unwrap(),
+///     "http://user@xn--bcher-kva.example:8080/ä?q=ü"
+/// );
+/// ```
+pub fn url_to_ascii(s: &str) -> Option<String> {
+    convert_host(s, host_to_ascii)
+}
+
+/// Finds the URL in a text and converts its host to Unicode.
+///
+/// # Arguments
+///
+/// * `s` - A URL, a text containing a URL, or a host name
+///
+/// # Returns
+///
+/// * `Some(String)` - The URL, with its host lowercased and Punycode-decoded
+/// * `None` - If no host is found, or it is not a valid domain name
+///
+/// # Examples
+///
+/// ```
+/// use url::idn::url_to_unicode;
+///
+/// assert_eq!(url_to_unicode("xn--85x722f.xn--55qx5d.cn").unwrap(), "食狮.公司.cn");
+/// assert_eq!(url_to_unicode("https://Example.org/A").unwrap(), "https://example.org/A");
+/// ```
+pub fn url_to_unicode(s: &str) -> Option<String> {
+    convert_host(s, host_to_unicode)
+}
+
+/// Finds the URL in a text and checks whether its host is a homograph.
+///
+/// This is the UDF entry point for `urlIsHomograph`. See [`is_homograph`].
+///
+/// # Arguments
+///
+/// * `s` - A URL, a text containing a URL, or a host name
+///
+/// # Returns
+///
+/// * `Some("true")` - If the host contains lookalikes of Latin letters
+/// * `Some("false")` - If it does not, or it is an IP address
+/// * `None` - If no host is found, or the text is neither a URL nor a valid host name
+pub fn url_is_homograph(s: &str) -> Option<String> {
+    let host = host_of(s)?;
+    if url_parts(s).is_none() && host_to_unicode(&host).is_none() {
+        return None;
+    }
+    Some(is_homograph(&host).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for (unicode, ascii) in [
+            ("münchen.de", "xn--mnchen-3ya.de"),
+            ("пример.рф", "xn--e1afmkfd.xn--p1ai"),
+            ("www.example.org", "www.example.org"),
+        ] {
+            assert_eq!(host_to_ascii(unicode).unwrap(), ascii);
+            assert_eq!(host_to_unicode(ascii).unwrap(), unicode);
+        }
+    }
+
+    #[test]
+    fn test_uts46_mapping() {
+        assert_eq!(
+            host_to_ascii("ＭÜＮＣＨＥＮ.DE").unwrap(),
+            "xn--mnchen-3ya.de"
+        );
+        assert_eq!(host_to_ascii("faß.de").unwrap(), "xn--fa-hia.de");
+        assert!(host_to_ascii("a b.de").is_none());
+        assert!(host_to_unicode("xn--zz.de").is_none());
+    }
+
+    #[test]
+    fn test_url_conversion() {
+        assert_eq!(
+            url_to_ascii("Visit https://münchen.de/karte.").unwrap(),
+            "https://xn--mnchen-3ya.de/karte"
+        );
+        assert_eq!(
+            url_to_unicode("mirror at https://xn--mnchen-3ya.de:8080/a").unwrap(),
+            "https://münchen.de:8080/a"
+        );
+        assert_eq!(url_to_unicode("XN--MNCHEN-3YA.DE").unwrap(), "münchen.de");
+        assert!(url_to_ascii("http://[::1]:80/").is_none());
+        assert!(url_to_ascii("no url here").is_none());
+    }
+
+    #[test]
+    fn test_is_homograph() {
+        assert!(is_homograph("аррӏе.com"));
+        assert!(is_homograph("www.аррӏе.com"));
+        assert!(is_homograph("gοοgle.com"));
+        assert!(is_homograph("xn--zz.com"));
+
+        assert!(!is_homograph("google.com"));
+        assert!(!is_homograph("münchen.de"));
+        assert!(is_homograph("ουτ.com"));
+        assert!(!is_homograph("ουτ.gr"));
+        assert!(!is_homograph("сос.рф"));
+        assert!(!is_homograph("россия.com"));
+        assert!(!is_homograph("ドメイン名例.jp"));
+        assert!(!is_homograph("abc食狮.cn"));
+    }
+
+    #[test]
+    fn test_url_is_homograph() {
+        assert_eq!(
+            url_is_homograph("login at https://аррӏе.com/id").unwrap(),
+            "true"
+        );
+        assert_eq!(url_is_homograph("https://apple.com/id").unwrap(), "false");
+        assert!(url_is_homograph("").is_none());
+    }
+
+    #[test]
+    fn test_url_is_homograph_ip() {
+        assert_eq!(url_is_homograph("http://[::1]/").unwrap(), "false");
+        assert_eq!(
+            url_is_homograph("https://[2001:db8::1]:8443/x").unwrap(),
+            "false"
+        );
+        assert_eq!(url_is_homograph("http://1.2.3.4/").unwrap(), "false");
+    }
+
+    #[test]
+    fn test_url_is_homograph_not_a_host() {
+        assert!(url_is_homograph("%").is_none());
+        assert!(url_is_homograph("1.2.3[.]4").is_none());
+    }
+}
@@ -0,0 +1,220 @@
+//! IP address extraction.
+//!
+//! IPv4 addresses are recognized in dotted-quad form (`192.168.1.10`, also as
+//! `192.168.1.10:8080`), and IPv6 addresses in any of their textual forms,
+//! compressed (`2001:db8::1`), full, with an embedded IPv4 address
+//! (`::ffff:192.0.2.1`) or in brackets with a port (`[2001:db8::1]:443`).
+//!
+//! Addresses are only recognized as whole words, so versions (`1.2.3.4.5`),
+//! times (`12:30:45`) and MAC addresses are left out, and octets with leading
+//! zeros (`01.2.3.4`) are rejected as ambiguous.
+//!
+//! # Examples
+//!
+//! ```
+//! use url::ip::extract_ips;
+//!
+//! assert_eq!(
+//!     extract_ips("from 10.0.0.1:22 to [2001:DB8:0:0::1]:443", false),
+//!     vec!["10.0.0.1", "2001:db8::1"]
+//! );
+//! ```
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Detects the IP addresses of a text, in textual order.
+///
+/// # Arguments
+///
+/// * `s` - The input string to search for IP addresses
+///
+/// # Returns
+///
+/// The `(start, end)` byte indices of each address, with the parsed address.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::IpAddr;
+/// use url::ip::detect_ips;
+///
+/// let ips = detect_ips("ping ::1 and 127.0.0.1");
+/// assert_eq!(ips[0], (5, 8, "::1".parse::<IpAddr>().unwrap()));
+/// assert_eq!(ips[1].0, 13);
+/// ```
+pub fn detect_ips(s: &str) -> Vec<(usize, usize, IpAddr)> {
+    let mut ips: Vec<(usize, usize, IpAddr)> = ipv6_spans(s)
+        .into_iter()
+        .map(|(start, end, ip)| (start, end, IpAddr::V6(ip)))
+        .collect();
+
+    // IPv4 addresses embedded in IPv6 ones are part of them
+    for (start, end) in ipv4_spans(s) {
+        if !ips.iter().any(|&(a, b, _)| start < b && a < end) {
+            let ip: Ipv4Addr = s[start..end].parse().expect("Checked by ipv4_spans");
+            ips.push((start, end, IpAddr::V4(ip)));
+        }
+    }
+
+    ips.sort_by_key(|&(start, _, _)| start);
+    ips
+}
+
+/// Extracts the IP addresses of a text, in their normalized form.
+///
+/// IPv6 addresses are compressed and lowercased as recommended by RFC 5952.
+///
+/// # Arguments
+///
+/// * `s` - The input string to search for IP addresses
+/// * `distinct` - Whether to return a repeated address only once
+///
+/// # Returns
+///
+/// The normalized addresses, in textual order.
+///
+/// # Examples
+///
+/// ```
+/// use url::ip::extract_ips;
+///
+/// let text = "2001:0db8:0000:0000:0000:0000:0000:0001 and 2001:db8::1, 8.8.8.8.";
+/// assert_eq!(extract_ips(text, false), vec!["2001:db8::1", "2001:db8::1", "8.8.8.8"]);
+/// assert_eq!(extract_ips(text, true), vec!["2001:db8::1", "8.8.8.8"]);
+/// assert!(extract_ips("version 1.2.3", false).is_empty());
+/// ```
+pub fn extract_ips(s: &str, distinct: bool) -> Vec<String> {
+    let mut ips: Vec<String> = Vec::new();
+
+    for (_, _, ip) in detect_ips(s) {
+        let ip = ip.to_string();
+        if distinct && ips.contains(&ip) {
+            continue;
+        }
+        ips.push(ip);
+    }
+
+    ips
+}
+
+/// Returns the byte ranges of the dotted-quad IPv4 addresses of a text.
+pub(crate) fn ipv4_spans(s: &str) -> Vec<(usize, usize)> {
+    let bytes = s.as_bytes();
+    let is_word = |b: u8| b.is_ascii_alphanumeric() || b == b'.';
+    let mut spans = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if !bytes[i].is_ascii_digit() || (i > 0 && is_word(bytes[i - 1])) {
+            i += 1;
+            continue;
+        }
+
+        let mut end = i;
+        while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+            end += 1;
+        }
+        let next = end;
+        while end > i && bytes[end - 1] == b'.' {
+            end -= 1;
+        }
+
+        let standalone = next == end && !bytes.get(end).is_some_and(|&b| b.is_ascii_alphanumeric());
+        let dotted = next > end && !bytes.get(next).is_some_and(|&b| is_word(b));
+        if (standalone || dotted) && s[i..end].parse::<Ipv4Addr>().is_ok() {
+            spans.push((i, end));
+        }
+        i = next;
+    }
+
+    spans
+}
+
+/// Returns the byte ranges of the IPv6 addresses of a text, with the addresses.
+fn ipv6_spans(s: &str) -> Vec<(usize, usize, Ipv6Addr)> {
+    let bytes = s.as_bytes();
+    let is_part = |b: u8| b.is_ascii_hexdigit() || b == b':' || b == b'.';
+    let mut spans = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let after_word = i > 0 && (bytes[i - 1].is_ascii_alphanumeric() || is_part(bytes[i - 1]));
+        if !is_part(bytes[i]) || after_word {
+            i += 1;
+            continue;
+        }
+
+        let mut next = i;
+        while next < bytes.len() && is_part(bytes[next]) {
+            next += 1;
+        }
+
+        // A zone index (`fe80::1%eth0`) ends the address, a letter makes it a word
+        if !bytes
+            .get(next)
+            .is_some_and(|&b| b.is_ascii_alphanumeric() || b == b'_')
+        {
+            let candidate = s[i..next].trim_end_matches('.');
+            let ip = candidate
+                .parse::<Ipv6Addr>()
+                .ok()
+                .map(|ip| (candidate.len(), ip))
+                .or_else(|| {
+                    // Sentence punctuation (`on fe80::1:`)
+                    let trimmed = candidate.strip_suffix(':')?;
+                    Some((trimmed.len(), trimmed.parse().ok()?))
+                });
+
+            if let Some((len, ip)) = ip.filter(|_| candidate.bytes().any(|b| b.is_ascii_hexdigit()))
+            {
+                spans.push((i, i + len, ip));
+            }
+        }
+        i = next;
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ipv4_spans() {
+        let s = "1.2.3.4, 10.0.0.256 a1.2.3.4 1.2.3.4a 01.2.3.4 8.8.8.8.";
+        let ips: Vec<&str> = ipv4_spans(s).iter().map(|&(a, b)| &s[a..b]).collect();
+        assert_eq!(ips, vec!["1.2.3.4", "8.8.8.8"]);
+    }
+
+    #[test]
+    fn test_extract_ipv6() {
+        for (text, expected) in [
+            (
+                "addr 2001:db8:85a3::8a2e:370:7334.",
+                vec!["2001:db8:85a3::8a2e:370:7334"],
+            ),
+            (
+                "[FE80::1]:8080 and fe80::1%eth0",
+                vec!["fe80::1", "fe80::1"],
+            ),
+            ("mapped ::ffff:192.0.2.1", vec!["::ffff:192.0.2.1"]),
+            ("listening on :: and ::1:", vec!["::1"]),
+            ("at 12:30:45 from 00:1a:2b:3c:4d:5e", vec![]),
+            ("std::vector and fe80::1x", vec![]),
+            (
+                "1:2:3:4:5:6:7:8 and 1:2:3:4:5:6:7:8:9",
+                vec!["1:2:3:4:5:6:7:8"],
+            ),
+        ] {
+            assert_eq!(extract_ips(text, false), expected, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_detect_ips_order() {
+        let s = "b 2001:db8::2 a 192.0.2.1 c ::ffff:10.0.0.1";
+        let ips: Vec<&str> = detect_ips(s).iter().map(|&(a, b, _)| &s[a..b]).collect();
+        assert_eq!(ips, vec!["2001:db8::2", "192.0.2.1", "::ffff:10.0.0.1"]);
+    }
+}
@@ -1 +1,13 @@
+pub mod canonical;
+pub mod category;
+pub mod cidr;
+pub mod defang;
+pub mod domain;
+pub mod email;
+pub mod idn;
+pub mod ip;
+pub mod links;
+pub mod parts;
+pub mod psl;
+pub mod unwrap;
 pub mod url;
@@ -0,0 +1,566 @@
+//! Links of HTML and Markdown documents.
+//!
+//! [`extract_links`] finds the links of a document with their anchor text, where
+//! [`crate::url::extract_all_urls`] only sees the absolute URLs of plain text:
+//!
+//! | Form                            | Kind             | Text                     |
+//! |---------------------------------|------------------|--------------------------|
+//! | `<a href="url">text</a>`        | `html-link`      | The text, without tags   |
+//! | `<img src="url" alt="text">`    | `html-image`     | The `alt` attribute      |
+//! | `[text](url "title")`           | `markdown-link`  | The text between `[]`    |
+//! | `![text](url)`                  | `markdown-image` | The text between `[]`    |
+//!
+//! Tag and attribute names are case-insensitive, attribute values may be quoted
+//! or not, and character references (`&amp;`, `&#39;`) are decoded in values
+//! and text. Relative links are resolved against an optional base URL, as a
+//! browser does (RFC 3986 section 5.2).
+//!
+//! # Examples
+//!
+//! ```
+//! use url::links::{extract_links, Link, LinkKind};
+//!
+//! let html = r#"<p>See <a href="/docs?a=1&amp;b=2">the <b>docs</b></a></p>"#;
+//! assert_eq!(
+//!     extract_links(html, Some("https://example.org/blog/post")),
+//!     vec![Link {
+//!         url: "https://example.org/docs?a=1&b=2".to_string(),
+//!         text: "the docs".to_string(),
+//!         kind: LinkKind::HtmlLink,
+//!     }]
+//! );
+//! ```
+
+use crate::canonical::remove_dot_segments;
+
+/// Where a link was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    /// An HTML `<a href>` element.
+    HtmlLink,
+    /// An HTML `<img src>` element.
+    HtmlImage,
+    /// A Markdown `[text](url)` link.
+    MarkdownLink,
+    /// A Markdown `![text](url)` image.
+    MarkdownImage,
+}
+
+impl LinkKind {
+    /// Returns the name of the kind, as returned by the `extractLinks` UDF.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinkKind::HtmlLink => "html-link",
+            LinkKind::HtmlImage => "html-image",
+            LinkKind::MarkdownLink => "markdown-link",
+            LinkKind::MarkdownImage => "markdown-image",
+        }
+    }
+}
+
+/// A link of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Link {
+    /// The target, resolved against the base URL when there is one.
+    pub url: String,
+    /// The anchor text, or the alternative text of an image.
+    pub text: String,
+    /// The form of the link.
+    pub kind: LinkKind,
+}
+
+/// Extracts the HTML and Markdown links of a document, in textual order.
+///
+/// # Arguments
+///
+/// * `s` - The HTML or Markdown document
+/// * `base` - The absolute URL that relative links are resolved against, if any
+///
+/// # Returns
+///
+/// The links with a non-empty target. Without a base URL, or when the base URL
+/// is not absolute, relative links are returned as they are written.
+///
+/// # Examples
+///
+/// ```
+/// use url::links::{extract_links, LinkKind};
+///
+/// let markdown = "[![build](badge.svg)](https://ci.example.org/) and [docs](../docs/)";
+/// let links = extract_links(markdown, Some("https://example.org/repo/readme"));
+///
+/// assert_eq!(links.len(), 3);
+/// assert_eq!(links[0].url, "https://ci.example.org/");
+/// assert_eq!(links[0].text, "![build](badge.svg)");
+/// assert_eq!(links[1].url, "https://example.org/repo/badge.svg");
+/// assert_eq!(links[1].kind, LinkKind::MarkdownImage);
+/// assert_eq!(links[2].url, "https://example.org/docs/");
+/// ```
+pub fn extract_links(s: &str, base: Option<&str>) -> Vec<Link> {
+    let base = base
+        .map(str::trim)
+        .filter(|base| scheme_len(base).is_some());
+
+    let mut links: Vec<(usize, Link)> = html_links(s);
+    links.extend(markdown_links(s));
+    links.sort_by_key(|(start, _)| *start);
+
+    links
+        .into_iter()
+        .map(|(_, mut link)| {
+            if let Some(base) = base {
+                link.url = resolve(base, &link.url);
+            }
+            link
+        })
+        .collect()
+}
+
+/// Returns the `<a href>` and `<img src>` links of a document, with their positions.
+fn html_links(s: &str) -> Vec<(usize, Link)> {
+    let mut links = Vec::new();
+    let mut from = 0;
+
+    while let Some(start) = s[from..].find('<').map(|i| from + i) {
+        let rest = &s[start..];
+        if rest.starts_with("<!--") {
+            from = rest.find("-->").map_or(s.len(), |i| start + i + 3);
+            continue;
+        }
+
+        let Some((name, attributes, end)) = parse_tag(rest) else {
+            from = start + 1;
+            continue;
+        };
+        from = start + end;
+
+        let link = match name.as_str() {
+            "a" => attribute(attributes, "href").map(|url| {
+                let close = find_ignore_case(&s[from..], "</a").map_or(s.len(), |i| from + i);
+                let text = html_text(&s[from..close]);
+                (url, text, LinkKind::HtmlLink)
+            }),
+            "img" => attribute(attributes, "src").map(|url| {
+                let alt = attribute(attributes, "alt").unwrap_or_default();
+                (url, alt, LinkKind::HtmlImage)
+            }),
+            _ => None,
+        };
+
+        if let Some((url, text, kind)) = link.filter(|(url, _, _)| !url.is_empty()) {
+            links.push((start, Link { url, text, kind }));
+        }
+    }
+
+    links
+}
+
+/// Parses a start tag at the start of `s`.
+///
+/// # Returns
+///
+/// The lowercased tag name, the attributes and the byte length of the tag.
+fn parse_tag(s: &str) -> Option<(String, &str, usize)> {
+    let name_len = s[1..]
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(s.len() - 1);
+    if name_len == 0 {
+        return None;
+    }
+    let name = s[1..=name_len].to_ascii_lowercase();
+
+    // `>` may appear in quoted attribute values
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1 + name_len) {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some((name, &s[1 + name_len..i], i + 1)),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Returns the decoded value of an attribute, by case-insensitive name.
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '/');
+        if rest.is_empty() {
+            return None;
+        }
+
+        let name_len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(rest.len());
+        let attribute_name = &rest[..name_len];
+        rest = rest[name_len..].trim_start();
+
+        let value = match rest.strip_prefix('=') {
+            Some(after) => {
+                let after = after.trim_start();
+                let (value, len) = match after.chars().next() {
+                    Some(q @ ('"' | '\'')) => {
+                        let end = after[1..].find(q).map_or(after.len(), |i| i + 1);
+                        (&after[1..end], (end + 1).min(after.len()))
+                    }
+                    _ => {
+                        let end = after.find(char::is_whitespace).unwrap_or(after.len());
+                        (&after[..end], end)
+                    }
+                };
+                rest = &after[len..];
+                value
+            }
+            None => "",
+        };
+
+        if attribute_name.eq_ignore_ascii_case(name) {
+            return Some(decode_entities(value.trim()));
+        }
+        if name_len == 0 {
+            // Stray character, such as a lone `=`
+            rest = &rest[rest.chars().next().map_or(0, char::len_utf8)..];
+        }
+    }
+}
+
+/// Returns the position of an ASCII needle in a text, ignoring case.
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Returns the text of an HTML fragment, without tags and with collapsed whitespace.
+fn html_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        text.push(' ');
+        rest = &rest[start..];
+        let end = parse_tag(rest)
+            .map(|(_, _, end)| end)
+            .or_else(|| rest.find('>').map(|i| i + 1))
+            .unwrap_or(rest.len());
+        rest = &rest[end..];
+    }
+    text.push_str(rest);
+
+    decode_entities(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decodes the named character references of HTML's basic set and numeric ones.
+fn decode_entities(s: &str) -> String {
+    let mut decoded = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest[1..]
+            .find(';')
+            .filter(|&len| len <= 10)
+            .and_then(|len| Some((decode_entity(&rest[1..=len])?, len + 2)));
+
+        match entity {
+            Some((c, len)) => {
+                decoded.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Decodes the name of a character reference, without `&` and `;`.
+fn decode_entity(name: &str) -> Option<char> {
+    let code = if let Some(hex) = name.strip_prefix("#x").or_else(|| name.strip_prefix("#X")) {
+        u32::from_str_radix(hex, 16).ok()?
+    } else if let Some(decimal) = name.strip_prefix('#') {
+        decimal.parse().ok()?
+    } else {
+        return match name {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => None,
+        };
+    };
+
+    char::from_u32(code)
+}
+
+/// Returns the `[text](url)` and `![text](url)` links of a document, with their positions.
+///
+/// Images inside the text of a link, as in badges, are returned after the link.
+fn markdown_links(s: &str) -> Vec<(usize, Link)> {
+    let bytes = s.as_bytes();
+    let mut links = Vec::new();
+
+    for (open, _) in s.match_indices('[') {
+        if open > 0 && bytes[open - 1] == b'\\' {
+            continue;
+        }
+        let Some(close) = matching(s, open, b'[', b']') else {
+            continue;
+        };
+        if bytes.get(close + 1) != Some(&b'(') {
+            continue;
+        }
+        let Some(end) = matching(s, close + 1, b'(', b')') else {
+            continue;
+        };
+
+        let url = markdown_destination(&s[close + 2..end]);
+        if url.is_empty() {
+            continue;
+        }
+
+        let image = open > 0 && bytes[open - 1] == b'!';
+        let (start, kind) = match image {
+            true => (open - 1, LinkKind::MarkdownImage),
+            false => (open, LinkKind::MarkdownLink),
+        };
+        let text = s[open + 1..close].trim().to_string();
+        links.push((start, Link { url, text, kind }));
+    }
+
+    links
+}
+
+/// Returns the position of the bracket closing the one at `open`, on the same line.
+fn matching(s: &str, open: usize, left: u8, right: u8) -> Option<usize> {
+    let mut depth = 0;
+
+    for (i, &b) in s.as_bytes().iter().enumerate().skip(open) {
+        match b {
+            b'\n' => return None,
+            b'\\' => continue,
+            _ if b == left && (i == open || s.as_bytes()[i - 1] != b'\\') => depth += 1,
+            _ if b == right && s.as_bytes()[i - 1] != b'\\' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Returns the URL of a Markdown link destination, without its title.
+fn markdown_destination(destination: &str) -> String {
+    let destination = destination.trim();
+    let url = match destination.strip_prefix('<') {
+        Some(rest) => rest.split('>').next().unwrap_or_default(),
+        None => destination.split_whitespace().next().unwrap_or_default(),
+    };
+    decode_entities(url)
+}
+
+/// Returns the length of the scheme of an absolute URL, without `:`.
+fn scheme_len(url: &str) -> Option<usize> {
+    let len = url.find(':')?;
+    let scheme = &url[..len];
+    let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then_some(len)
+}
+
+/// Resolves a reference against an absolute base URL (RFC 3986 section 5.2).
+///
+/// # Examples
+///
+/// ```
+/// use url::links::resolve;
+///
+/// let base = "http://a/b/c/d;p?q";
+/// assert_eq!(resolve(base, "g"), "http://a/b/c/g");
+/// assert_eq!(resolve(base, "../g"), "http://a/b/g");
+/// assert_eq!(resolve(base, "/g"), "http://a/g");
+/// assert_eq!(resolve(base, "//g"), "http://g");
+/// assert_eq!(resolve(base, "?y"), "http://a/b/c/d;p?y");
+/// assert_eq!(resolve(base, "#s"), "http://a/b/c/d;p?q#s");
+/// assert_eq!(resolve(base, "mailto:x@example.org"), "mailto:x@example.org");
+/// ```
+pub fn resolve(base: &str, reference: &str) -> String {
+    if scheme_len(reference).is_some() {
+        return reference.to_string();
+    }
+    let Some(scheme_len) = scheme_len(base) else {
+        return reference.to_string();
+    };
+
+    let base = base.split('#').next().unwrap_or_default();
+    let scheme = &base[..=scheme_len];
+    if reference.starts_with("//") {
+        return format!("{}{}", scheme, reference);
+    }
+
+    // Split the base into `scheme:` `//authority` `path` `?query`
+    let hier = &base[scheme_len + 1..];
+    let (hier, query) = match hier.find('?') {
+        Some(i) => (&hier[..i], &hier[i..]),
+        None => (hier, ""),
+    };
+    let (authority, path) = match hier.strip_prefix("//") {
+        Some(rest) => {
+            let end = rest.find('/').unwrap_or(rest.len());
+            (&hier[..end + 2], &rest[end..])
+        }
+        None => ("", hier),
+    };
+    let prefix = format!("{}{}", scheme, authority);
+
+    if reference.is_empty() || reference.starts_with('#') {
+        return format!("{}{}{}{}", prefix, path, query, reference);
+    }
+    if reference.starts_with('?') {
+        return format!("{}{}{}", prefix, path, reference);
+    }
+
+    let end = reference.find(['?', '#']).unwrap_or(reference.len());
+    let (reference_path, rest) = reference.split_at(end);
+    let merged = if reference_path.starts_with('/') {
+        reference_path.to_string()
+    } else if !authority.is_empty() && path.is_empty() {
+        format!("/{}", reference_path)
+    } else {
+        let directory = path.rfind('/').map_or("", |i| &path[..=i]);
+        format!("{}{}", directory, reference_path)
+    };
+
+    let merged = match merged.starts_with('/') {
+        true => remove_dot_segments(&merged),
+        false => merged,
+    };
+    format!("{}{}{}", prefix, merged, rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(links: &[Link]) -> Vec<(&str, &str, &str)> {
+        links
+            .iter()
+            .map(|link| (link.url.as_str(), link.text.as_str(), link.kind.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_html_links() {
+        let html = r#"
+            <A HREF='https://example.org/a'>First&nbsp;link</A>
+            <a class="x" href=/b data-x="1>2">Second <img src="b.png" alt="Logo &amp; name"/></a>
+            <!-- <a href="/commented">no</a> -->
+            <a name="anchor">no href</a>
+            <a href="">empty</a>
+            <img alt=unquoted src=c.gif>
+            <area href="/not-a-link">
+        "#;
+        assert_eq!(
+            summary(&extract_links(html, None)),
+            vec![
+                ("https://example.org/a", "First link", "html-link"),
+                ("/b", "Second", "html-link"),
+                ("b.png", "Logo & name", "html-image"),
+                ("c.gif", "unquoted", "html-image"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown_links() {
+        let markdown = r#"
+A [link](https://example.org "Title") and ![alt text](img/a.png).
+[nested [brackets]](<path with spaces.md>) and [paren](https://en.wikipedia.org/wiki/Rust_(language)).
+Not links: \[escaped](x), [no destination], [empty](), [broken
+](x).
+"#;
+        assert_eq!(
+            summary(&extract_links(markdown, None)),
+            vec![
+                ("https://example.org", "link", "markdown-link"),
+                ("img/a.png", "alt text", "markdown-image"),
+                ("path with spaces.md", "nested [brackets]", "markdown-link"),
+                (
+                    "https://en.wikipedia.org/wiki/Rust_(language)",
+                    "paren",
+                    "markdown-link"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        // RFC 3986 section 5.4 examples
+        let base = "http://a/b/c/d;p?q";
+        for (reference, expected) in [
+            ("g:h", "g:h"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("//g", "http://g"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("g#s", "http://a/b/c/g#s"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../..", "http://a/"),
+            ("../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("g.", "http://a/b/c/g."),
+            ("g;x=1/../y", "http://a/b/c/y"),
+        ] {
+            assert_eq!(resolve(base, reference), expected, "{}", reference);
+        }
+
+        assert_eq!(
+            resolve("https://example.org", "a/b"),
+            "https://example.org/a/b"
+        );
+        assert_eq!(
+            resolve("https://example.org/x#frag", "#top"),
+            "https://example.org/x#top"
+        );
+        assert_eq!(resolve("not a base", "a/b"), "a/b");
+    }
+
+    #[test]
+    fn test_extract_links_with_base() {
+        let links = extract_links(
+            "<a href='../x'>x</a> [y](y.html)",
+            Some("https://example.org/docs/guide/"),
+        );
+        assert_eq!(links[0].url, "https://example.org/docs/x");
+        assert_eq!(links[1].url, "https://example.org/docs/guide/y.html");
+
+        let links = extract_links("[y](y.html)", Some("relative/base"));
+        assert_eq!(links[0].url, "y.html");
+    }
+}
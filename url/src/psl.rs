@@ -0,0 +1,442 @@
+//! Public Suffix List lookups.
+//!
+//! The [Public Suffix List](https://publicsuffix.org/) names the suffixes under
+//! which domains can be registered (`com`, `co.uk`, `github.io`, ...). A snapshot
+//! of the list is embedded from `src/public_suffix_list.dat`, which is distributed
+//! under the Mozilla Public License 2.0.
+//!
+//! # Sections
+//!
+//! The list has an ICANN section, for the suffixes of domain registries, and a
+//! private section, for suffixes under which companies hand out subdomains
+//! (`github.io`, `blogspot.co.uk`). Both are used by default, so the registered
+//! domain of `user.github.io` is `user.github.io`. With only the ICANN section
+//! ([`PublicSuffixList::with_private_domains`], or the `icann-only` argument of
+//! the UDF binaries) it is `github.io`.
+//!
+//! # Updating the List
+//!
+//! The embedded snapshot can be replaced without rebuilding by a newer copy of
+//! <https://publicsuffix.org/list/public_suffix_list.dat>:
+//!
+//! - From the `URL_PSL_PATH` environment variable
+//! - From a command-line argument of the UDF binaries, which takes precedence, e.g.
+//!   `<command>url-registered-domain /etc/clickhouse-server/public_suffix_list.dat</command>`
+//!
+//! # Internationalized Domain Names
+//!
+//! Rules and hosts are compared in their ASCII form, so `食狮.com.cn` and
+//! `xn--85x722f.com.cn` have the same public suffix. Results keep the form of the
+//! host they are taken from, lowercased.
+//!
+//! # Examples
+//!
+//! ```
+//! use url::psl::public_suffix_list;
+//!
+//! let psl = public_suffix_list();
+//! assert_eq!(psl.suffix_labels("www.example.co.uk"), Some(2));
+//! assert_eq!(psl.suffix_labels("file.txt"), None);
+//! assert_eq!(psl.registered_domain("www.Example.CO.UK").unwrap(), "example.co.uk");
+//! ```
+
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Environment variable with the path of a list replacing the embedded one.
+pub const PSL_PATH_ENV: &str = "URL_PSL_PATH";
+
+static PSL_DATA: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/",
+    "src/public_suffix_list.dat"
+));
+
+static PUBLIC_SUFFIX_LIST: OnceLock<PublicSuffixList> = OnceLock::new();
+static ICANN_SUFFIX_LIST: OnceLock<PublicSuffixList> = OnceLock::new();
+
+/// The section of the list a rule comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    /// Suffixes of domain registries (`co.uk`).
+    Icann,
+    /// Suffixes under which companies hand out subdomains (`github.io`).
+    Private,
+}
+
+/// The rules of a Public Suffix List.
+#[derive(Debug, Clone)]
+pub struct PublicSuffixList {
+    /// Plain rules (`co.uk`).
+    rules: HashMap<String, Section>,
+    /// Parents of wildcard rules (`ck` for `*.ck`).
+    wildcards: HashMap<String, Section>,
+    /// Exception rules without their `!` (`www.ck`).
+    exceptions: HashMap<String, Section>,
+    /// Whether rules of the private section are used.
+    private_domains: bool,
+}
+
+impl PublicSuffixList {
+    /// Parses the embedded snapshot of the list.
+    pub fn embedded() -> Self {
+        Self::parse(PSL_DATA)
+    }
+
+    /// Parses a list in the `public_suffix_list.dat` format.
+    ///
+    /// Each line holds one rule, and only its first word is read. Comments start
+    /// with `//`, and the `===BEGIN ICANN DOMAINS===` and `===BEGIN PRIVATE
+    /// DOMAINS===` comments start the sections. Rules before any section are
+    /// ICANN rules. Rules are stored lowercased and in ASCII form.
+    pub fn parse(data: &str) -> Self {
+        let mut psl = PublicSuffixList {
+            rules: HashMap::new(),
+            wildcards: HashMap::new(),
+            exceptions: HashMap::new(),
+            private_domains: true,
+        };
+        let mut section = Section::Icann;
+
+        for line in data.lines() {
+            let Some(rule) = line.split_whitespace().next() else {
+                continue;
+            };
+            if rule.starts_with("//") {
+                if line.contains("===BEGIN ICANN DOMAINS===") {
+                    section = Section::Icann;
+                } else if line.contains("===BEGIN PRIVATE DOMAINS===") {
+                    section = Section::Private;
+                }
+                continue;
+            }
+
+            let (rules, rule) = if let Some(parent) = rule.strip_prefix("*.") {
+                (&mut psl.wildcards, parent)
+            } else if let Some(exception) = rule.strip_prefix('!') {
+                (&mut psl.exceptions, exception)
+            } else {
+                (&mut psl.rules, rule)
+            };
+            if let Some(rule) = to_ascii(rule) {
+                rules.insert(rule, section);
+            }
+        }
+
+        psl
+    }
+
+    /// Reads a list from a file in the `public_suffix_list.dat` format.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read public suffix list: {}", path.display()))?;
+
+        let psl = Self::parse(&data);
+        if psl.rules.is_empty() {
+            bail!("No rules in public suffix list: {}", path.display());
+        }
+
+        Ok(psl)
+    }
+
+    /// Sets whether rules of the private section are used (the default).
+    pub fn with_private_domains(mut self, private_domains: bool) -> Self {
+        self.private_domains = private_domains;
+        self
+    }
+
+    /// Returns the number of labels of the public suffix of a domain.
+    ///
+    /// Only rules of the list are used: a domain whose top-level label is not
+    /// listed has no public suffix, rather than the implicit `*` rule of the
+    /// specification. This is what tells `example.org` from `file.txt`.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - A domain name, in any case, without trailing dot
+    ///
+    /// # Returns
+    ///
+    /// * `Some(n)` - The last `n` labels of the domain are its public suffix
+    /// * `None` - If no rule of the list matches the domain
+    pub fn suffix_labels(&self, domain: &str) -> Option<usize> {
+        let domain = to_ascii(domain)?;
+        let mut labels = 0;
+        let mut parent: Option<&str> = None;
+
+        let starts = std::iter::once(0).chain(domain.match_indices('.').map(|(i, _)| i + 1));
+        for start in starts.rev() {
+            let suffix = &domain[start..];
+            if suffix.is_empty() || suffix.starts_with('.') {
+                return None;
+            }
+
+            if self.has_rule(&self.exceptions, suffix) {
+                return Some(suffix.split('.').count() - 1).filter(|&n| n > 0);
+            }
+            if self.has_rule(&self.rules, suffix)
+                || parent.is_some_and(|p| self.has_rule(&self.wildcards, p))
+            {
+                labels = suffix.split('.').count();
+            }
+            parent = Some(suffix);
+        }
+
+        (labels > 0).then_some(labels)
+    }
+
+    /// Returns the public suffix of a host name (`co.uk` for `www.example.co.uk`).
+    ///
+    /// A top-level domain missing from the list is its own public suffix, as
+    /// with the implicit `*` rule of the specification.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(String)` - The public suffix, lowercased
+    /// * `None` - If the host is an IP address or not a valid domain name
+    pub fn public_suffix(&self, host: &str) -> Option<String> {
+        let (host, n) = self.split_host(host)?;
+        Some(last_labels(&host, n).to_string())
+    }
+
+    /// Returns the registered domain (eTLD+1) of a host name (`example.co.uk`
+    /// for `www.example.co.uk`).
+    ///
+    /// # Returns
+    ///
+    /// * `Some(String)` - The public suffix and the label before it, lowercased
+    /// * `None` - If the host is itself a public suffix, an IP address, or not a
+    ///   valid domain name
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use url::psl::PublicSuffixList;
+    ///
+    /// let psl = PublicSuffixList::embedded();
+    /// assert_eq!(psl.registered_domain("user.github.io").unwrap(), "user.github.io");
+    /// assert_eq!(psl.registered_domain("www.食狮.公司.cn").unwrap(), "食狮.公司.cn");
+    /// assert!(psl.registered_domain("co.uk").is_none());
+    ///
+    /// let psl = psl.with_private_domains(false);
+    /// assert_eq!(psl.registered_domain("user.github.io").unwrap(), "github.io");
+    /// ```
+    pub fn registered_domain(&self, host: &str) -> Option<String> {
+        let (host, n) = self.split_host(host)?;
+        (host.split('.').count() > n).then(|| last_labels(&host, n + 1).to_string())
+    }
+
+    /// Returns the labels of a host name before its registered domain (`www`
+    /// for `www.example.co.uk`), or an empty string when there are none.
+    ///
+    /// # Returns
+    ///
+    /// * `Some(String)` - The subdomain, lowercased
+    /// * `None` - If the host has no registered domain
+    pub fn subdomain(&self, host: &str) -> Option<String> {
+        let (host, n) = self.split_host(host)?;
+        let domain = last_labels(&host, n + 1);
+
+        (host.split('.').count() > n).then(|| {
+            host[..host.len() - domain.len()]
+                .trim_end_matches('.')
+                .to_string()
+        })
+    }
+
+    /// Returns whether a rule is listed, in a section in use.
+    fn has_rule(&self, rules: &HashMap<String, Section>, rule: &str) -> bool {
+        rules
+            .get(rule)
+            .is_some_and(|&section| self.private_domains || section == Section::Icann)
+    }
+
+    /// Lowercases a host name and returns it with the number of labels of its
+    /// public suffix, the implicit `*` rule included.
+    fn split_host(&self, host: &str) -> Option<(String, usize)> {
+        let host = host.trim_end_matches('.').to_lowercase();
+        let is_ip = host.starts_with('[') || host.parse::<std::net::Ipv4Addr>().is_ok();
+        if is_ip || host.is_empty() || host.split('.').any(str::is_empty) {
+            return None;
+        }
+
+        let ascii = to_ascii(&host)?;
+        let n = self.suffix_labels(&ascii).unwrap_or(1);
+
+        // Mapping to ASCII can turn other dots (`。`) into `.`, then use the ASCII form
+        match ascii.split('.').count() == host.split('.').count() {
+            true => Some((host, n)),
+            false => Some((ascii, n)),
+        }
+    }
+}
+
+/// Converts a domain name to lowercase ASCII, with Punycode for non-ASCII labels.
+fn to_ascii(domain: &str) -> Option<String> {
+    match domain.is_ascii() {
+        true => Some(domain.to_ascii_lowercase()),
+        false => idna::domain_to_ascii(domain).ok(),
+    }
+}
+
+/// Returns the last `n` labels of a domain name.
+fn last_labels(domain: &str, n: usize) -> &str {
+    match domain.rmatch_indices('.').nth(n.saturating_sub(1)) {
+        Some((i, _)) => &domain[i + 1..],
+        None => domain,
+    }
+}
+
+/// Initializes the global list from the arguments of a UDF binary.
+///
+/// `icann-only` leaves out the private section, and any other argument is the
+/// path of a list replacing the embedded one. Without a path, the list named by
+/// `URL_PSL_PATH` is used if set. Calling it after the first lookup returns an error.
+pub fn init_public_suffix_list(args: &[String]) -> Result<()> {
+    let mut private_domains = true;
+    let mut psl = None;
+
+    for arg in args {
+        match arg.as_str() {
+            "icann-only" => private_domains = false,
+            path => psl = Some(PublicSuffixList::from_file(path)?),
+        }
+    }
+
+    let psl = match psl {
+        Some(psl) => psl,
+        None => load_from_env()?,
+    };
+
+    if PUBLIC_SUFFIX_LIST
+        .set(psl.with_private_domains(private_domains))
+        .is_err()
+    {
+        bail!("Public suffix list is already initialized");
+    }
+
+    Ok(())
+}
+
+/// Returns the global list.
+///
+/// If [`init_public_suffix_list`] was not called, this is the list named by
+/// `URL_PSL_PATH`, or the embedded one. Errors reading that file are logged to
+/// stderr and the embedded list is used.
+pub fn public_suffix_list() -> &'static PublicSuffixList {
+    PUBLIC_SUFFIX_LIST.get_or_init(|| {
+        load_from_env().unwrap_or_else(|e| {
+            eprintln!("ERROR: {:#}", e);
+            PublicSuffixList::embedded()
+        })
+    })
+}
+
+/// Returns the global list without its private section.
+///
+/// Rules matching on the registered domain of a brand (`google.*`) use it, so
+/// that `google.blogspot.com` is a subdomain of `blogspot.com` rather than a
+/// domain registered by Google.
+pub(crate) fn icann_suffix_list() -> &'static PublicSuffixList {
+    ICANN_SUFFIX_LIST.get_or_init(|| public_suffix_list().clone().with_private_domains(false))
+}
+
+fn load_from_env() -> Result<PublicSuffixList> {
+    match std::env::var_os(PSL_PATH_ENV).filter(|path| !path.is_empty()) {
+        Some(path) => PublicSuffixList::from_file(path),
+        None => Ok(PublicSuffixList::embedded()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suffix_labels() {
+        let psl = public_suffix_list();
+        assert_eq!(psl.suffix_labels("example.org"), Some(1));
+        assert_eq!(psl.suffix_labels("WWW.Example.CO.UK"), Some(2));
+        assert_eq!(psl.suffix_labels("user.github.io"), Some(2));
+        assert_eq!(psl.suffix_labels("org"), Some(1));
+        assert_eq!(psl.suffix_labels("file.txt"), None);
+        assert_eq!(psl.suffix_labels("v1.2"), None);
+        assert_eq!(psl.suffix_labels(""), None);
+        assert_eq!(psl.suffix_labels("example..org"), None);
+    }
+
+    #[test]
+    fn test_suffix_labels_wildcards_and_exceptions() {
+        let psl = PublicSuffixList::parse("// comment\nck\n*.ck\n!www.ck\n");
+        assert_eq!(psl.suffix_labels("a.b.ck"), Some(2));
+        assert_eq!(psl.suffix_labels("b.ck"), Some(2));
+        assert_eq!(psl.suffix_labels("www.ck"), Some(1));
+        assert_eq!(psl.suffix_labels("a.www.ck"), Some(1));
+        assert_eq!(psl.suffix_labels("comment"), None);
+    }
+
+    #[test]
+    fn test_sections() {
+        let data = "// ===BEGIN ICANN DOMAINS===\nio\nuk\nco.uk\n\
+                    // ===BEGIN PRIVATE DOMAINS===\ngithub.io\nblogspot.co.uk\n";
+        let psl = PublicSuffixList::parse(data);
+        assert_eq!(psl.public_suffix("a.b.github.io").unwrap(), "github.io");
+        assert_eq!(
+            psl.registered_domain("a.blogspot.co.uk").unwrap(),
+            "a.blogspot.co.uk"
+        );
+
+        let psl = psl.with_private_domains(false);
+        assert_eq!(psl.public_suffix("a.b.github.io").unwrap(), "io");
+        assert_eq!(
+            psl.registered_domain("a.blogspot.co.uk").unwrap(),
+            "blogspot.co.uk"
+        );
+    }
+
+    #[test]
+    fn test_registered_domain_and_subdomain() {
+        let psl = PublicSuffixList::embedded();
+        assert_eq!(
+            psl.registered_domain("a.b.example.com.au").unwrap(),
+            "example.com.au"
+        );
+        assert_eq!(psl.subdomain("a.b.example.com.au").unwrap(), "a.b");
+        assert_eq!(psl.subdomain("example.com.au").unwrap(), "");
+        assert_eq!(psl.public_suffix("host.unknowntld").unwrap(), "unknowntld");
+        assert_eq!(
+            psl.registered_domain("a.host.unknowntld").unwrap(),
+            "host.unknowntld"
+        );
+        assert_eq!(psl.public_suffix("example.org.").unwrap(), "org");
+
+        assert!(psl.registered_domain("localhost").is_none());
+        assert!(psl.subdomain("com.au").is_none());
+        assert!(psl.public_suffix("192.168.0.1").is_none());
+        assert!(psl.public_suffix("[::1]").is_none());
+        assert!(psl.public_suffix("a..org").is_none());
+    }
+
+    #[test]
+    fn test_idn() {
+        let psl = PublicSuffixList::embedded();
+        assert_eq!(psl.public_suffix("www.食狮.公司.cn").unwrap(), "公司.cn");
+        assert_eq!(
+            psl.public_suffix("www.xn--85x722f.xn--55qx5d.cn").unwrap(),
+            "xn--55qx5d.cn"
+        );
+        assert_eq!(
+            psl.registered_domain("WWW.MÜNCHEN.DE").unwrap(),
+            "münchen.de"
+        );
+        assert_eq!(psl.subdomain("a.b。example.org").unwrap(), "a.b");
+    }
+
+    #[test]
+    fn test_from_file() {
+        assert!(PublicSuffixList::from_file("/nonexistent/psl.dat").is_err());
+    }
+}
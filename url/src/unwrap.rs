@@ -0,0 +1,388 @@
+//! Offline unwrapping of tracking redirects.
+//!
+//! Links in emails and chats are often wrapped by a redirector that records the
+//! click before sending the browser to the destination: Google `/url?q=`, Outlook
+//! SafeLinks, Facebook `l.php`, LinkedIn, Slack, Proofpoint URL Defense, ...
+//! [`url_unwrap`] decodes the destination from the wrapper URL itself, without
+//! any network access, and repeats while the destination is itself wrapped.
+//! Shorteners such as `bit.ly` cannot be unwrapped this way.
+//!
+//! # Rules
+//!
+//! The embedded `src/unwrap_rules.csv` lists the known wrappers, one per line:
+//!
+//! ```text
+//! host,path,target,decoding
+//! google.*,/url,q|url,percent
+//! *.safelinks.protection.outlook.com,/,url,percent
+//! href.li,/,@query,percent
+//! urldefense.proofpoint.com,/v2/url,u,proofpoint-v2
+//! ```
+//!
+//! - `host` - An exact host, `*.suffix` for its subdomains, or `name.*` for a
+//!   registered domain under any public suffix of a domain registry (`google.com`,
+//!   `google.co.uk`, but not `google.blogspot.com`)
+//! - `path` - The path prefix of the wrapper
+//! - `target` - The query parameters holding the destination, separated by `|`,
+//!   or `@path` for the rest of the URL after the path prefix, or `@query` for
+//!   the whole query string
+//! - `decoding` - `percent` (the default) or `proofpoint-v2`
+//!
+//! Lines starting with `#` are comments. More rules can be loaded from files in
+//! the same format, taking precedence over the embedded ones:
+//!
+//! - From the `URL_UNWRAP_RULES_PATH` environment variable (several paths separated by `:`)
+//! - From command-line arguments of `url-unwrap`, e.g.
+//!   `<command>url-unwrap /etc/clickhouse-server/unwrap_rules.csv</command>`
+
+use anyhow::{bail, Context, Result};
+use shared::table::GlobalTable;
+use std::path::Path;
+
+use crate::parts::{parse_url, percent_decode};
+use crate::psl::icann_suffix_list;
+use crate::url::detect_url;
+
+/// Environment variable listing extra rule files to load.
+pub const UNWRAP_RULES_PATH_ENV: &str = "URL_UNWRAP_RULES_PATH";
+
+/// Maximum number of nested wrappers removed from a URL
+const MAX_DEPTH: usize = 8;
+
+static UNWRAP_RULES_DATA: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/",
+    "src/unwrap_rules.csv"
+));
+
+static UNWRAPPER: GlobalTable<Unwrapper> = GlobalTable::new(
+    "URL unwrapper",
+    UNWRAP_RULES_PATH_ENV,
+    Unwrapper::embedded,
+    |unwrapper, path| unwrapper.load_file(path),
+);
+
+/// Where a wrapper URL holds its destination.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    /// The first of these query parameters present in the URL.
+    Params(Vec<String>),
+    /// The rest of the URL after the path prefix.
+    Path,
+    /// The whole query string.
+    Query,
+}
+
+/// How the destination is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoding {
+    /// Percent-encoding (`https%3A%2F%2F...`), or none.
+    Percent,
+    /// Proofpoint URL Defense v2: `-` for `%` and `_` for `/` (`https-3A__...`).
+    ProofpointV2,
+}
+
+/// A known redirect wrapper.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnwrapRule {
+    /// Exact host, `*.suffix` or `name.*`.
+    pub host: String,
+    /// Path prefix of the wrapper.
+    pub path: String,
+    /// Where the destination is.
+    pub target: Target,
+    /// How the destination is encoded.
+    pub decoding: Decoding,
+}
+
+impl UnwrapRule {
+    /// Parses one `host,path,target,decoding` line.
+    fn parse(line: &str) -> Result<Self> {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [host, path, target, decoding @ ..] = fields.as_slice() else {
+            bail!("Expected host,path,target[,decoding]: {:?}", line);
+        };
+
+        let target = match *target {
+            "@path" => Target::Path,
+            "@query" => Target::Query,
+            "" => bail!("Missing target: {:?}", line),
+            params => Target::Params(params.split('|').map(str::to_string).collect()),
+        };
+        let decoding = match decoding.first().copied().unwrap_or_default() {
+            "" | "percent" => Decoding::Percent,
+            "proofpoint-v2" => Decoding::ProofpointV2,
+            other => bail!("Unknown decoding {:?}: {:?}", other, line),
+        };
+
+        Ok(UnwrapRule {
+            host: host.to_lowercase(),
+            path: path.to_string(),
+            target,
+            decoding,
+        })
+    }
+
+    /// Returns whether the rule applies to a host.
+    fn matches_host(&self, host: &str) -> bool {
+        if let Some(suffix) = self.host.strip_prefix("*.") {
+            host.strip_suffix(suffix)
+                .is_some_and(|sub| sub.ends_with('.'))
+        } else if let Some(name) = self.host.strip_suffix(".*") {
+            icann_suffix_list()
+                .registered_domain(host)
+                .and_then(|domain| domain.strip_prefix(name).map(|s| s.starts_with('.')))
+                .unwrap_or(false)
+        } else {
+            host == self.host
+        }
+    }
+
+    /// Returns the destination of a wrapper URL, if the rule applies to it.
+    fn unwrap(&self, url: &str) -> Option<String> {
+        let parts = parse_url(url)?;
+        if !self.matches_host(&parts.host) || !parts.path.starts_with(&self.path) {
+            return None;
+        }
+
+        let encoded = match &self.target {
+            Target::Params(names) => names.iter().find_map(|name| {
+                parts
+                    .query
+                    .iter()
+                    .find(|(key, value)| key == name && !value.is_empty())
+                    .map(|(_, value)| value.clone())
+            })?,
+            Target::Path => {
+                let path_start = url.find("://")? + 3;
+                let path_start = path_start + url[path_start..].find('/')?;
+                url[path_start + self.path.len()..].to_string()
+            }
+            Target::Query => url.split_once('?')?.1.to_string(),
+        };
+
+        let decoded = match self.decoding {
+            Decoding::ProofpointV2 => percent_decode(&encoded.replace('-', "%").replace('_', "/")),
+            Decoding::Percent if encoded.contains("://") => encoded,
+            Decoding::Percent => percent_decode(&encoded),
+        };
+
+        if is_destination(&decoded) {
+            Some(decoded)
+        } else {
+            // Destinations in paths may leave out the scheme (`/amp/s/example.org/page`)
+            let decoded = format!("https://{}", decoded);
+            is_destination(&decoded).then_some(decoded)
+        }
+    }
+}
+
+/// Returns whether a decoded destination is a URL with a host.
+fn is_destination(url: &str) -> bool {
+    !url.contains(char::is_whitespace) && parse_url(url).is_some_and(|parts| !parts.host.is_empty())
+}
+
+/// The embedded wrapper rules with the rules loaded from files.
+#[derive(Debug, Clone, Default)]
+pub struct Unwrapper {
+    rules: Vec<UnwrapRule>,
+}
+
+impl Unwrapper {
+    /// Creates an unwrapper with the embedded `unwrap_rules.csv` rules.
+    pub fn embedded() -> Self {
+        let rules = parse_rules(UNWRAP_RULES_DATA).expect("Invalid embedded unwrap rules");
+        Unwrapper { rules }
+    }
+
+    /// Loads a rule file, whose rules take precedence over the existing ones.
+    ///
+    /// # Returns
+    ///
+    /// The number of rules loaded from the file.
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<usize> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read unwrap rules: {}", path.display()))?;
+        let rules = parse_rules(&content)
+            .with_context(|| format!("Failed to parse unwrap rules: {}", path.display()))?;
+
+        let count = rules.len();
+        self.rules.splice(0..0, rules);
+
+        Ok(count)
+    }
+
+    /// Removes the wrappers around a URL.
+    ///
+    /// # Returns
+    ///
+    /// The destination of the outermost wrapper, after at most eight nested
+    /// wrappers, or the URL itself when no rule applies.
+    pub fn unwrap_url(&self, url: &str) -> String {
+        let mut url = url.to_string();
+
+        for _ in 0..MAX_DEPTH {
+            match self.rules.iter().find_map(|rule| rule.unwrap(&url)) {
+                Some(destination) => url = destination,
+                None => break,
+            }
+        }
+
+        url
+    }
+}
+
+/// Parses rule lines, skipping comments, blank lines and the header.
+fn parse_rules(content: &str) -> Result<Vec<UnwrapRule>> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter(|line| !line.starts_with("host,"))
+        .map(UnwrapRule::parse)
+        .collect()
+}
+
+/// Initializes the global unwrapper from `URL_UNWRAP_RULES_PATH` and the given
+/// rule files, before the first lookup. See [`GlobalTable::init`].
+pub fn init_unwrapper<I, P>(paths: I) -> Result<()>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    UNWRAPPER.init(paths)
+}
+
+/// Returns the global unwrapper, with the embedded rules unless set with
+/// [`init_unwrapper`].
+fn unwrapper() -> &'static Unwrapper {
+    UNWRAPPER.get()
+}
+
+/// Finds the URL in a text and returns its destination, without redirect wrappers.
+///
+/// # Arguments
+///
+/// * `s` - The input string to search for a URL
+///
+/// # Returns
+///
+/// * `Some(String)` - The destination URL, or the URL itself when it is not wrapped
+/// * `None` - If no URL is found
+///
+/// # Examples
+///
+/// ```
+/// use url::unwrap::url_unwrap;
+///
+/// assert_eq!(
+///     url_unwrap("https://www.google.com/url?q=https%3A%2F%2Fexample.org%2Fa%3Fb%3D1&sa=D").unwrap(),
+///     "https://example.org/a?b=1"
+/// );
+/// assert_eq!(
+///     url_unwrap("see https://l.facebook.com/l.php?u=https%3A%2F%2Fexample.org%2F&h=AT0").unwrap(),
+///     "https://example.org/"
+/// );
+/// assert_eq!(url_unwrap("https://example.org/").unwrap(), "https://example.org/");
+/// ```
+pub fn url_unwrap(s: &str) -> Option<String> {
+    let (start, end) = detect_url(s)?;
+    Some(unwrapper().unwrap_url(&s[start..end]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unwrap_known_wrappers() {
+        let unwrapper = Unwrapper::embedded();
+        for (wrapped, expected) in [
+            (
+                "https://www.google.co.uk/url?sa=t&url=https%3A%2F%2Fexample.org%2F",
+                "https://example.org/",
+            ),
+            (
+                "https://eur01.safelinks.protection.outlook.com/?url=https%3A%2F%2Fexample.org%2Fdoc&data=05%7C01",
+                "https://example.org/doc",
+            ),
+            (
+                "https://www.linkedin.com/redir/redirect?url=https%3A%2F%2Fexample.org&urlhash=x",
+                "https://example.org",
+            ),
+            (
+                "https://slack-redir.net/link?url=http%3A%2F%2Fexample.org%2Fpage",
+                "http://example.org/page",
+            ),
+            (
+                "https://urldefense.proofpoint.com/v2/url?u=https-3A__example.org_path-3Fa-3D1&d=DwMF",
+                "https://example.org/path?a=1",
+            ),
+            (
+                "https://www.google.com/amp/s/example.org/news/story.amp",
+                "https://example.org/news/story.amp",
+            ),
+            (
+                "https://href.li/?https://example.org/a?b=1",
+                "https://example.org/a?b=1",
+            ),
+        ] {
+            assert_eq!(unwrapper.unwrap_url(wrapped), expected, "{}", wrapped);
+        }
+    }
+
+    #[test]
+    fn test_unwrap_nested() {
+        let google = "https://www.google.com/url?q=https%3A%2F%2Fl.facebook.com%2Fl.php%3Fu%3Dhttps%253A%252F%252Fexample.org%252F";
+        assert_eq!(
+            Unwrapper::embedded().unwrap_url(google),
+            "https://example.org/"
+        );
+    }
+
+    #[test]
+    fn test_unwrap_not_wrapped() {
+        let unwrapper = Unwrapper::embedded();
+        for url in [
+            "https://www.google.com/search?q=rust",
+            "https://www.google.com/url?q=not+a+url",
+            "https://notgoogle.com/url?q=https%3A%2F%2Fexample.org",
+            "https://example.org/url?q=https%3A%2F%2Fexample.com",
+            "https://safelinks.protection.outlook.com.evil.org/?url=https%3A%2F%2Fexample.org",
+            "https://google.herokuapp.com/url?q=https%3A%2F%2Fexample.org",
+            "https://google.blogspot.com/url?q=https%3A%2F%2Fexample.org",
+        ] {
+            assert_eq!(unwrapper.unwrap_url(url), url);
+        }
+    }
+
+    #[test]
+    fn test_rule_parse() {
+        let rule = UnwrapRule::parse("go.example.com, /out, to|dest").unwrap();
+        assert_eq!(
+            rule.target,
+            Target::Params(vec!["to".into(), "dest".into()])
+        );
+        assert_eq!(rule.decoding, Decoding::Percent);
+
+        assert!(UnwrapRule::parse("go.example.com,/out").is_err());
+        assert!(UnwrapRule::parse("go.example.com,/out,to,base64").is_err());
+    }
+
+    #[test]
+    fn test_load_file() {
+        let path = std::env::temp_dir().join(format!("url-unwrap-{}.csv", std::process::id()));
+        std::fs::write(&path, "# custom\ngo.example.com,/out,to\n").unwrap();
+
+        let mut unwrapper = Unwrapper::embedded();
+        assert_eq!(unwrapper.load_file(&path).unwrap(), 1);
+        assert_eq!(
+            unwrapper.unwrap_url("https://go.example.com/out?to=https%3A%2F%2Fexample.org"),
+            "https://example.org"
+        );
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(unwrapper.load_file("/nonexistent/rules.csv").is_err());
+    }
+}
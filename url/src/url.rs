@@ -1,16 +1,37 @@
 //! URL detection and extraction utilities.
 //!
 //! This module provides functions for detecting, extracting, and validating URLs
-//! within text strings. It supports common protocols including HTTP, HTTPS, FTP,
-//! FTPS, and FILE.
+//! within text strings. URLs are recognized by their scheme, from a configurable
+//! registry, and optionally by bare domain names such as `www.example.com/path`
+//! or `example.co.uk`.
 //!
-//! # Supported Protocols
+//! # Supported Schemes
 //!
-//! - `http://`
-//! - `https://`
-//! - `ftp://`
-//! - `ftps://`
-//! - `file://`
+//! - `http://`, `https://`, `ftp://`, `ftps://`, `sftp://`, `file://`
+//! - `ws://`, `wss://`, `ssh://`, `git://`
+//! - `s3://`, `gs://`
+//! - `mailto:`, `tel:`, `magnet:`
+//!
+//! Schemes are matched case-insensitively and only at the start of a word, so
+//! `hotel:` is not a `tel:` link. More schemes can be added with
+//! [`UrlDetector::with_scheme`], or as arguments of the UDF binaries:
+//!
+//! ```xml
+//! <command>extract-url rtsp:// slack: bare-domains</command>
+//! ```
+//!
+//! # Bare Domains
+//!
+//! Host names without a scheme are only URLs with [`UrlDetector::with_bare_domains`]
+//! or the `bare-domains` argument of the UDF binaries, since prose is full of
+//! words joined by a dot (`ASP.NET`, `end.It was`). They are recognized when their
+//! top-level domain is in
+//! the embedded Public Suffix List (see [`crate::psl`]) and they have a label
+//! before their public suffix. This keeps `file.txt`, `v1.2` and `1.2.3.4` out,
+//! as well as file names whose extension is also a country code (`readme.md`,
+//! `main.rs`) unless the host starts with `www.`. Internationalized domain names
+//! (`münchen.de`, `食狮.公司.cn`) are recognized too, but not a capitalized
+//! top-level domain, which starts a sentence missing its space (`end.It`).
 //!
 //! # Examples
 //!
@@ -27,16 +48,31 @@
 //!
 //! // Check if text contains a URL
 //! assert_eq!(has_url("https://example.org").unwrap(), "true");
-//! assert_eq!(has_url("no url here").unwrap(), "false");
+//! assert_eq!(has_url("see www.example.com/path").unwrap(), "false");
 //! ```
 
-/// Static array of URL protocol patterns
-const URL_PATTERNS: &[&str] = &["http://", "https://", "ftp://", "ftps://", "file://"];
+use anyhow::{bail, Result};
+use std::sync::OnceLock;
+
+use crate::psl::public_suffix_list;
+
+/// Schemes recognized by default, with their `//` when they have an authority
+pub const DEFAULT_SCHEMES: &[&str] = &[
+    "http://", "https://", "ftp://", "ftps://", "sftp://", "file://", "ws://", "wss://", "ssh://",
+    "git://", "s3://", "gs://", "mailto:", "tel:", "magnet:",
+];
+
+/// Top-level domains that are more often file extensions in text than domains
+const FILE_EXTENSIONS: &[&str] = &["cc", "md", "mov", "pl", "ps", "py", "rs", "sh", "so", "zip"];
+
+/// Characters removed from the end of a URL, as sentence punctuation rather than part of it
+const TRAILING_PUNCTUATION: &[char] = &['?', '!', '.', ',', ':', ';', '*', '_', '~', '\''];
 
 /// Detects the position of a URL within a string.
 ///
 /// Searches for the first occurrence of a recognized URL protocol and determines
-/// the boundaries of the complete URL by finding the next whitespace character.
+/// the boundaries of the complete URL, leaving out the punctuation, brackets and
+/// quotes that surround URLs in prose, Markdown and HTML.
 ///
 /// # Arguments
 ///
@@ -45,15 +81,18 @@ const URL_PATTERNS: &[&str] = &["http://", "https://", "ftp://", "ftps://", "fil
 /// # Returns
 ///
 /// * `Some((start, end))` - Tuple of byte indices marking the URL boundaries
-///   - `start`: Index where the URL begins (start of protocol)
-///   - `end`: Index where the URL ends (at whitespace or end of string)
+///   - `start`: Index where the URL begins (start of scheme or host)
+///   - `end`: Index where the URL ends
 /// * `None` - If no URL is found
 ///
 /// # URL Detection Logic
 ///
-/// 1. Searches for the first matching protocol from `URL_PATTERNS`
-/// 2. Finds the end of the URL by locating the next whitespace character
-/// 3. If no whitespace is found, extends to the end of the string
+/// 1. Searches for the earliest scheme of the registry (see [`UrlDetector`]), or
+///    bare domain name when enabled
+/// 2. Finds the end of the URL at the next whitespace, `<`, `>`, `"` or `` ` ``,
+///    at the closing quote of a quoted URL, or at the end of a Markdown link
+/// 3. Removes trailing punctuation (`.`, `,`, `!`, ...), closing parentheses and
+///    brackets without an opening match, and a trailing entity reference
 ///
 /// # Examples
 ///
@@ -75,24 +114,297 @@ const URL_PATTERNS: &[&str] = &["http://", "https://", "ftp://", "ftps://", "fil
 ///     Some((0, 28))
 /// );
 ///
+/// // Punctuation and parentheses around the URL
+/// assert_eq!(
+///     detect_url("(see https://example.org/foo)."),
+///     Some((5, 28))
+/// );
+///
+/// // The first URL of the text, whatever its protocol
+/// assert_eq!(detect_url("ftp://a.org then http://b.org"), Some((0, 11)));
+///
 /// // No URL found
 /// assert_eq!(detect_url("no url here"), None);
 /// ```
 pub fn detect_url(s: &str) -> Option<(usize, usize)> {
-    for &pattern in URL_PATTERNS {
-        match s.find(pattern) {
-            Some(pos) => {
-                let remaining = &s[pos + pattern.len()..];
-                let end_offset = remaining
-                    .find(char::is_whitespace)
-                    .unwrap_or(remaining.len());
-                return Some((pos, pos + pattern.len() + end_offset));
+    detect_url_from(s, 0)
+}
+
+/// Detects the positions of all URLs within a string, in textual order.
+///
+/// URLs never overlap: the search for the next URL starts where the previous one ends.
+///
+/// # Arguments
+///
+/// * `s` - The input string to search for URLs
+///
+/// # Returns
+///
+/// The `(start, end)` byte indices of each URL, as returned by [`detect_url`].
+///
+/// # Examples
+///
+/// ```ignore
+/// use url::url::detect_urls;
+///
+/// assert_eq!(
+///     detect_urls("ftp://a.org and http://b.org"),
+///     vec![(0, 11), (16, 28)]
+/// );
+/// assert!(detect_urls("no url here").is_empty());
+/// ```
+pub fn detect_urls(s: &str) -> Vec<(usize, usize)> {
+    url_detector().detect_all(s)
+}
+
+/// Detects the first URL starting at or after byte `from`, with the global detector.
+fn detect_url_from(s: &str, from: usize) -> Option<(usize, usize)> {
+    url_detector().detect_from(s, from)
+}
+
+static URL_DETECTOR: OnceLock<UrlDetector> = OnceLock::new();
+
+/// Sets the detector used by [`detect_url`] and the other functions of this module.
+///
+/// # Errors
+///
+/// Fails if the detector was already initialized.
+pub fn init_url_detector(detector: UrlDetector) -> Result<()> {
+    if URL_DETECTOR.set(detector).is_err() {
+        bail!("URL detector is already initialized");
+    }
+
+    Ok(())
+}
+
+/// Returns the global detector, the default one unless set with [`init_url_detector`].
+pub(crate) fn url_detector() -> &'static UrlDetector {
+    URL_DETECTOR.get_or_init(UrlDetector::default)
+}
+
+/// The schemes and kinds of URLs to recognize in text.
+#[derive(Debug, Clone)]
+pub struct UrlDetector {
+    /// Lowercased schemes, with `://` or `:`.
+    schemes: Vec<String>,
+    /// Whether host names without a scheme are URLs.
+    bare_domains: bool,
+}
+
+impl Default for UrlDetector {
+    fn default() -> Self {
+        UrlDetector {
+            schemes: DEFAULT_SCHEMES.iter().map(|s| s.to_string()).collect(),
+            bare_domains: false,
+        }
+    }
+}
+
+impl UrlDetector {
+    /// Creates a detector for [`DEFAULT_SCHEMES`], without bare domains.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a detector from the arguments of a UDF binary.
+    ///
+    /// Arguments ending with `:` or `://` add a scheme, and `bare-domains` turns
+    /// on bare-domain detection (`no-bare-domains` turns it off again). Other
+    /// arguments are left to the binary.
+    pub fn from_args(args: &[String]) -> Self {
+        let mut detector = Self::default();
+
+        for arg in args {
+            if arg == "bare-domains" {
+                detector = detector.with_bare_domains(true);
+            } else if arg == "no-bare-domains" {
+                detector = detector.with_bare_domains(false);
+            } else if arg.len() > 1 && arg.ends_with(':') || arg.len() > 3 && arg.ends_with("://") {
+                detector = detector.with_scheme(arg);
+            }
+        }
+
+        detector
+    }
+
+    /// Adds a scheme, written with its `://` (`rtsp://`) or `:` (`slack:`).
+    pub fn with_scheme(mut self, scheme: &str) -> Self {
+        let scheme = scheme.to_ascii_lowercase();
+        if !self.schemes.contains(&scheme) {
+            self.schemes.push(scheme);
+        }
+        self
+    }
+
+    /// Sets whether host names without a scheme are URLs (off by default).
+    pub fn with_bare_domains(mut self, bare_domains: bool) -> Self {
+        self.bare_domains = bare_domains;
+        self
+    }
+
+    /// Detects the first URL starting at or after byte `from`.
+    ///
+    /// See [`detect_url`] for the boundaries of the URL.
+    pub fn detect_from(&self, s: &str, from: usize) -> Option<(usize, usize)> {
+        let mut prev = s[..from].chars().next_back();
+
+        for (i, c) in s[from..].char_indices() {
+            let start = from + i;
+            if c.is_alphanumeric() && prev.is_none_or(|p| !p.is_alphanumeric()) {
+                if let Some(body_start) = self.scheme_at(s, start) {
+                    return Some((start, url_end(s, start, body_start)));
+                }
+                if self.bare_domains && !prev.is_some_and(|p| ".-@/:_".contains(p)) {
+                    if let Some(end) = bare_domain_at(s, start) {
+                        return Some((start, end));
+                    }
+                }
+            }
+            prev = Some(c);
+        }
+
+        None
+    }
+
+    /// Detects all URLs of a text, see [`detect_urls`].
+    pub fn detect_all(&self, s: &str) -> Vec<(usize, usize)> {
+        let mut urls = Vec::new();
+        let mut from = 0;
+
+        while let Some((start, end)) = self.detect_from(s, from) {
+            urls.push((start, end));
+            from = end;
+        }
+
+        urls
+    }
+
+    /// Returns where the body of a URL starts, if a scheme starts at byte `start`.
+    fn scheme_at(&self, s: &str, start: usize) -> Option<usize> {
+        let rest = &s.as_bytes()[start..];
+
+        self.schemes.iter().find_map(|scheme| {
+            let body_start = start + scheme.len();
+            let matches = rest.len() > scheme.len()
+                && rest[..scheme.len()].eq_ignore_ascii_case(scheme.as_bytes())
+                && !s[body_start..].starts_with(char::is_whitespace);
+            matches.then_some(body_start)
+        })
+    }
+}
+
+/// Returns the end of the URL, if a bare domain name starts at byte `start`.
+///
+/// The domain may be followed by a port, and then by a path, query or fragment.
+fn bare_domain_at(s: &str, start: usize) -> Option<usize> {
+    let rest = &s[start..];
+    let run = rest
+        .find(|c: char| !(c.is_alphanumeric() || c == '.' || c == '-'))
+        .unwrap_or(rest.len());
+    if rest[run..].starts_with(['@', '_']) {
+        return None;
+    }
+
+    let host = rest[..run].trim_end_matches(['.', '-']);
+    if !is_bare_domain(host) {
+        return None;
+    }
+
+    let mut end = start + host.len();
+    if host.len() == run {
+        if let Some(port) = s[end..].strip_prefix(':') {
+            let digits = port
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(port.len());
+            if digits > 0 {
+                end += digits + 1;
             }
-            None => continue,
+        }
+        if s[end..].starts_with(['/', '?', '#']) {
+            end = url_end(s, start, end);
+        }
+    }
+
+    Some(end)
+}
+
+/// Returns whether a host name without a scheme is a domain with a public suffix.
+fn is_bare_domain(host: &str) -> bool {
+    let labels: Vec<&str> = host.split('.').collect();
+    let valid_labels = labels.iter().all(|label| {
+        !label.is_empty() && label.len() <= 63 && !label.starts_with('-') && !label.ends_with('-')
+    });
+    let tld = labels[labels.len() - 1].to_ascii_lowercase();
+
+    // A capitalized word after a dot starts a sentence (`end.It was`)
+    let mut tld_chars = labels[labels.len() - 1].chars();
+    let capitalized =
+        tld_chars.next().is_some_and(char::is_uppercase) && tld_chars.any(char::is_lowercase);
+
+    valid_labels
+        && labels.len() > 1
+        && !capitalized
+        && !tld.chars().all(|c| c.is_ascii_digit())
+        && (host.starts_with("www.") || !FILE_EXTENSIONS.contains(&tld.as_str()))
+        && public_suffix_list()
+            .suffix_labels(host)
+            .is_some_and(|n| labels.len() > n)
+}
+
+/// Finds the end of the URL starting at byte `start`, whose scheme ends at `body_start`.
+///
+/// The URL ends at whitespace, at characters never found unencoded in URLs
+/// (`<`, `>`, `"`, `` ` ``), at the closing quote when the URL is quoted
+/// (`'https://a.b'`), and at `](` or `]` in Markdown links. Then, as for GitHub
+/// Flavored Markdown autolinks, trailing punctuation, unbalanced closing
+/// parentheses and brackets, and a trailing entity reference (`&amp;`) are removed.
+fn url_end(s: &str, start: usize, body_start: usize) -> usize {
+    let opener = s[..start].chars().next_back();
+    let closer = match opener {
+        Some('\'') => Some('\''),
+        Some('[') => Some(']'),
+        _ => None,
+    };
+
+    let body = &s[body_start..];
+    let mut end = body
+        .find(|c: char| {
+            c.is_whitespace() || matches!(c, '<' | '>' | '"' | '`') || Some(c) == closer
+        })
+        .unwrap_or(body.len());
+    if let Some(link_end) = body[..end].find("](") {
+        end = link_end;
+    }
+
+    let mut url = &body[..end];
+    loop {
+        let trimmed = url.trim_end_matches(TRAILING_PUNCTUATION);
+        if let Some(entity) = trailing_entity(url) {
+            url = &url[..url.len() - entity];
+        } else if trimmed.len() != url.len() {
+            url = trimmed;
+        } else if is_unbalanced(url, '(', ')') || is_unbalanced(url, '[', ']') {
+            url = &url[..url.len() - 1];
+        } else {
+            break;
         }
     }
 
-    None
+    body_start + url.len()
+}
+
+/// Returns whether a URL ends with a closing character that has no opening match.
+fn is_unbalanced(url: &str, open: char, close: char) -> bool {
+    url.ends_with(close) && url.matches(close).count() > url.matches(open).count()
+}
+
+/// Returns the length of an entity reference (`&amp;`) at the end of a URL.
+fn trailing_entity(url: &str) -> Option<usize> {
+    let name = url.strip_suffix(';')?;
+    let amp = name.rfind('&')?;
+    let name = &name[amp + 1..];
+
+    (!name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric())).then_some(name.len() + 2)
 }
 
 /// Extracts the URL from a string, if one exists.
@@ -136,6 +448,46 @@ pub fn extract_url(s: &str) -> Option<String> {
     detect_url(s).map(|(start, end)| s[start..end].to_string())
 }
 
+/// Extracts all URLs from a string, in textual order.
+///
+/// # Arguments
+///
+/// * `s` - The input string to search for URLs
+/// * `distinct` - Whether to return a repeated URL only once, at its first position
+///
+/// # Returns
+///
+/// Each URL with the byte offset (0-based) of its first character in `s`.
+///
+/// # Examples
+///
+/// ```ignore
+/// use url::url::extract_all_urls;
+///
+/// let text = "ftp://a.org http://b.org ftp://a.org";
+/// assert_eq!(
+///     extract_all_urls(text, true),
+///     vec![
+///         ("ftp://a.org".to_string(), 0),
+///         ("http://b.org".to_string(), 12),
+///     ]
+/// );
+/// assert_eq!(extract_all_urls(text, false).len(), 3);
+/// ```
+pub fn extract_all_urls(s: &str, distinct: bool) -> Vec<(String, usize)> {
+    let mut urls: Vec<(String, usize)> = Vec::new();
+
+    for (start, end) in detect_urls(s) {
+        let url = &s[start..end];
+        if distinct && urls.iter().any(|(seen, _)| seen == url) {
+            continue;
+        }
+        urls.push((url.to_string(), start));
+    }
+
+    urls
+}
+
 /// Checks whether a string contains a URL.
 ///
 /// Returns a string representation of a boolean value ("true" or "false")
@@ -228,6 +580,126 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_detect_url_earliest_protocol() {
+        assert_eq!(
+            extract_url("ftp://a.example.org then http://b.example.org"),
+            Some("ftp://a.example.org".to_string())
+        );
+        assert_eq!(
+            extract_url("file:///tmp/x or https://example.org"),
+            Some("file:///tmp/x".to_string())
+        );
+    }
+
+    #[test]
+    fn test_url_detector_schemes() {
+        let detector = UrlDetector::new();
+        assert_eq!(
+            detector.detect_from("go ws://a.example.org", 0),
+            Some((3, 21))
+        );
+        assert_eq!(detector.detect_from("hotel:5 stars", 0), None);
+        assert_eq!(detector.detect_from("mailto: nobody", 0), None);
+        assert_eq!(detector.detect_from("rtsp://cam.local/1", 0), None);
+        assert_eq!(detector.detect_from("www.example.org", 0), None);
+
+        let detector = detector.with_scheme("RTSP://");
+        assert_eq!(detector.detect_from("rtsp://cam.local/1", 0), Some((0, 18)));
+    }
+
+    #[test]
+    fn test_url_detector_from_args() {
+        let args = ["slack:", "distinct", "bare-domains", ":"].map(String::from);
+        let detector = UrlDetector::from_args(&args);
+        assert_eq!(detector.detect_from("open slack:team", 0), Some((5, 15)));
+        assert_eq!(detector.detect_from("example.org", 0), Some((0, 11)));
+        assert!(!detector.schemes.contains(&":".to_string()));
+
+        let detector = UrlDetector::from_args(&[]);
+        assert_eq!(detector.detect_from("example.org", 0), None);
+
+        let args = ["bare-domains", "no-bare-domains"].map(String::from);
+        let detector = UrlDetector::from_args(&args);
+        assert_eq!(detector.detect_from("example.org", 0), None);
+    }
+
+    #[test]
+    fn test_bare_domains() {
+        let detector = UrlDetector::new().with_bare_domains(true);
+        let find = |s: &'static str| detector.detect_from(s, 0).map(|(a, b)| &s[a..b]);
+
+        assert_eq!(
+            find("see www.example.com/path."),
+            Some("www.example.com/path")
+        );
+        assert_eq!(find("example.co.uk"), Some("example.co.uk"));
+        assert_eq!(find("blog.Example.ORG:443"), Some("blog.Example.ORG:443"));
+        for text in [
+            "file.txt",
+            "v1.2",
+            "10.0.0.1",
+            "main.rs",
+            "co.uk",
+            "a..example.org",
+            "-a.example.org-",
+            "e.g. this",
+            "end.It was",
+        ] {
+            assert_eq!(find(text), None, "{:?}", text);
+        }
+        assert_eq!(find("www.main.rs"), Some("www.main.rs"));
+
+        // Off by default
+        assert_eq!(has_url("see www.example.com/path").unwrap(), "false");
+        assert_eq!(has_url("ASP.NET rocks").unwrap(), "false");
+    }
+
+    #[test]
+    fn test_extract_all_urls() {
+        let text = "ftp://a.org http://b.org\thttps://c.org http://b.org";
+        assert_eq!(
+            extract_all_urls(text, false),
+            vec![
+                ("ftp://a.org".to_string(), 0),
+                ("http://b.org".to_string(), 12),
+                ("https://c.org".to_string(), 25),
+                ("http://b.org".to_string(), 39),
+            ]
+        );
+        assert_eq!(extract_all_urls(text, true).len(), 3);
+        assert!(extract_all_urls("", false).is_empty());
+        assert!(extract_all_urls("no url", true).is_empty());
+    }
+
+    #[test]
+    fn test_url_boundaries_corpus() {
+        check_corpus(
+            include_str!("../testdata/boundaries.tsv"),
+            &UrlDetector::new(),
+        );
+        check_corpus(
+            include_str!("../testdata/bare_domains.tsv"),
+            &UrlDetector::new().with_bare_domains(true),
+        );
+    }
+
+    fn check_corpus(corpus: &str, detector: &UrlDetector) {
+        for line in corpus.lines() {
+            if line.starts_with('#') {
+                continue;
+            }
+            let (input, expected) = line.split_once('\t').expect("input<TAB>expected");
+            let found = detector.detect_from(input, 0).map(|(a, b)| &input[a..b]);
+            assert_eq!(
+                found.unwrap_or_default(),
+                expected,
+                "boundaries of {:?}",
+                input
+            );
+        }
+    }
+
     #[test]
     fn test_has_url() {
         for (input, expected) in TEST_CASES.iter() {
@@ -1,4 +1,6 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use vin::decode::vin_decode;
+use vin::generate::{Corruption, VinGenerator};
 use vin::vin::{vin_cleaner, vin_manuf, vin_year};
 
 fn vin_cleaner_benchmark(c: &mut Criterion) {
@@ -55,10 +57,62 @@ fn vin_manuf_benchmark(c: &mut Criterion) {
     group.finish();
 }
 
+fn vin_decode_benchmark(c: &mut Criterion) {
+    let inputs = [
+        "G1ND52F14M700000 (bla bla)",
+        " this is long long long long long long input G1ND52F14M700000 and long long long extra things",
+        "G1ND52F14M700000",
+        "invalid",
+        "",
+    ];
+
+    let mut group = c.benchmark_group("vin_decode");
+    for input in inputs.iter() {
+        group.bench_with_input(BenchmarkId::from_parameter(input), &input, |b, &i| {
+            b.iter(|| vin_decode(i));
+        });
+    }
+    group.finish();
+}
+
+fn vin_generated_benchmark(c: &mut Criterion) {
+    let generator = VinGenerator::new("", 2010..=2024, 2024)
+        .unwrap()
+        .with_corruptions(
+            &[
+                Corruption::Case,
+                Corruption::Whitespace,
+                Corruption::Ioq,
+                Corruption::Truncate,
+            ],
+            0.5,
+        );
+    let inputs: Vec<String> = (0..1000)
+        .map(|seed| generator.generate_seeded(seed))
+        .collect();
+
+    let mut group = c.benchmark_group("vin_generated");
+    group.bench_function("vin_generate", |b| {
+        b.iter(|| generator.generate_seeded(42));
+    });
+    group.bench_function("vin_cleaner", |b| {
+        b.iter(|| inputs.iter().filter_map(|i| vin_cleaner(i)).count());
+    });
+    group.bench_function("vin_year", |b| {
+        b.iter(|| inputs.iter().filter_map(|i| vin_year(i)).count());
+    });
+    group.bench_function("vin_decode", |b| {
+        b.iter(|| inputs.iter().filter_map(|i| vin_decode(i)).count());
+    });
+    group.finish();
+}
+
 criterion_group!(
     benches,
     vin_cleaner_benchmark,
     vin_year_benchmark,
-    vin_manuf_benchmark
+    vin_manuf_benchmark,
+    vin_decode_benchmark,
+    vin_generated_benchmark
 );
 criterion_main!(benches);
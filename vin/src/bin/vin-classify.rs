@@ -0,0 +1,9 @@
+use anyhow::Result;
+use shared::io::process_stdin;
+use vin::legacy::vin_classify;
+
+fn main() -> Result<()> {
+    process_stdin(Box::new(vin_classify));
+
+    Ok(())
+}
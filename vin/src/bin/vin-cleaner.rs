@@ -1,9 +1,17 @@
 use anyhow::Result;
-use shared::io::process_stdin;
-use vin::vin::vin_cleaner;
+use shared::io::{args, process_stdin};
+use vin::vin::{vin_cleaner, vin_cleaner_strict};
 
 fn main() -> Result<()> {
-    process_stdin(Box::new(vin_cleaner));
+    // `strict` keeps only VINs that pass check digit validation
+    let strict = args().first().is_some_and(|arg| arg == "strict");
+    let cleaner: fn(&str) -> Option<String> = if strict {
+        vin_cleaner_strict
+    } else {
+        vin_cleaner
+    };
+
+    process_stdin(Box::new(cleaner));
 
     Ok(())
 }
@@ -0,0 +1,13 @@
+use anyhow::Result;
+use shared::format::{quote, tuple};
+use shared::io::process_stdin;
+use vin::vin::vin_country;
+
+fn main() -> Result<()> {
+    // Returns a ClickHouse Tuple(code String, name String)
+    process_stdin(Box::new(|s| {
+        vin_country(s).map(|(code, name)| tuple([quote(code), quote(name)]))
+    }));
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use anyhow::Result;
+use shared::io::{args, process_stdin_send_chunk_header};
+use vin::decode::vin_decode;
+use vin::wmi_db::init_wmi_database;
+
+fn main() -> Result<()> {
+    // Extra WMI files can be passed as arguments
+    init_wmi_database(args())?;
+
+    process_stdin_send_chunk_header(Box::new(vin_decode));
+
+    Ok(())
+}
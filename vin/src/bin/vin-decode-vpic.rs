@@ -0,0 +1,12 @@
+use anyhow::Result;
+use shared::io::{args, process_stdin};
+use vin::vpic::{init_vpic_index, vin_decode_vpic};
+
+fn main() -> Result<()> {
+    // The vPIC index can be passed as argument, otherwise VIN_VPIC_PATH is used
+    init_vpic_index(args().first())?;
+
+    process_stdin(Box::new(vin_decode_vpic));
+
+    Ok(())
+}
@@ -0,0 +1,24 @@
+use anyhow::Result;
+use shared::format::{array, quote, tuple};
+use shared::io::{args, process_stdin};
+use vin::extract::vin_extract_all;
+
+fn main() -> Result<()> {
+    // `offsets` returns Array(Tuple(vin String, offset UInt64)) instead of Array(String)
+    let offsets = args().first().is_some_and(|arg| arg == "offsets");
+
+    process_stdin(Box::new(move |s| {
+        let vins = vin_extract_all(s);
+
+        Some(if offsets {
+            array(
+                vins.iter()
+                    .map(|(vin, offset)| tuple([quote(vin), offset.to_string()])),
+            )
+        } else {
+            array(vins.iter().map(|(vin, _)| quote(vin)))
+        })
+    }));
+
+    Ok(())
+}
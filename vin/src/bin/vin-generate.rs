@@ -0,0 +1,24 @@
+use anyhow::Result;
+use chrono::{Datelike, Local};
+use shared::io::{args, process_stdin};
+use vin::generate::{vin_generate_with, Corruption};
+
+fn main() -> Result<()> {
+    // Corruptions to apply (case, whitespace, ioq, truncate) and the share of
+    // corrupted VINs (rate=0.1, all of them by default)
+    let mut corruptions = Vec::new();
+    let mut rate = 1.0;
+    for arg in args() {
+        match arg.strip_prefix("rate=") {
+            Some(value) => rate = value.parse()?,
+            None => corruptions.push(arg.parse::<Corruption>()?),
+        }
+    }
+
+    let reference_year = Local::now().year();
+    process_stdin(Box::new(move |s| {
+        vin_generate_with(s, reference_year, &corruptions, rate)
+    }));
+
+    Ok(())
+}
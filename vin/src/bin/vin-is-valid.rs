@@ -0,0 +1,9 @@
+use anyhow::Result;
+use shared::io::process_stdin;
+use vin::vin::vin_is_valid;
+
+fn main() -> Result<()> {
+    process_stdin(Box::new(vin_is_valid));
+
+    Ok(())
+}
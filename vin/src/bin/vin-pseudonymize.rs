@@ -0,0 +1,13 @@
+use anyhow::Result;
+use shared::io::{args, process_stdin};
+use vin::pseudonym::{init_pseudonym_key, vin_pseudonymize};
+
+fn main() -> Result<()> {
+    // The key file can be passed as argument, otherwise VIN_PSEUDONYM_KEY_FILE
+    // or VIN_PSEUDONYM_KEY is used
+    init_pseudonym_key(args().first())?;
+
+    process_stdin(Box::new(vin_pseudonymize));
+
+    Ok(())
+}
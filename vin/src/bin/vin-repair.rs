@@ -0,0 +1,13 @@
+use anyhow::Result;
+use shared::format::{quote, tuple};
+use shared::io::process_stdin;
+use vin::repair::vin_repair;
+
+fn main() -> Result<()> {
+    // Returns a ClickHouse Tuple(vin String, status String)
+    process_stdin(Box::new(|s| {
+        vin_repair(s).map(|(vin, status)| tuple([quote(vin), quote(status)]))
+    }));
+
+    Ok(())
+}
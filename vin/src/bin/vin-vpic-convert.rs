@@ -0,0 +1,26 @@
+//! Converts a CSV export of the NHTSA vPIC database into the index read by
+//! `vin-decode-vpic`. See the `vin::vpic` module for the export query.
+//!
+//! Usage: vin-vpic-convert <vpic.csv> <vpic.bin>
+
+use anyhow::{bail, Context, Result};
+use shared::io::args;
+use std::fs::File;
+use vin::vpic::VpicIndex;
+
+fn main() -> Result<()> {
+    let [input, output] = args().try_into().unwrap_or_default();
+    if input.is_empty() || output.is_empty() {
+        bail!("Usage: vin-vpic-convert <vpic.csv> <vpic.bin>");
+    }
+
+    let file = File::open(&input).with_context(|| format!("Failed to open {}", input))?;
+    let index = VpicIndex::from_csv(file).with_context(|| format!("Failed to parse {}", input))?;
+
+    let file = File::create(&output).with_context(|| format!("Failed to create {}", output))?;
+    index.write_to(file)?;
+
+    eprintln!("Wrote {} patterns to {}", index.len(), output);
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use anyhow::Result;
+use shared::io::process_stdin;
+use vin::vin::{reference_year, vin_year_at};
+
+fn main() -> Result<()> {
+    // Arguments: vin, reference year (or Date/DateTime)
+    process_stdin(Box::new(|s| {
+        let (vin, reference) = s.split_once('\t')?;
+        vin_year_at(vin, reference_year(reference)?)
+    }));
+
+    Ok(())
+}
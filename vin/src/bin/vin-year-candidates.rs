@@ -0,0 +1,17 @@
+use anyhow::Result;
+use shared::format::array;
+use shared::io::process_stdin;
+use vin::vin::{reference_year, vin_year_candidates};
+
+fn main() -> Result<()> {
+    // Arguments: vin, reference year (or Date/DateTime)
+    // Returns a ClickHouse Array(UInt16)
+    process_stdin(Box::new(|s| {
+        let (vin, reference) = s.split_once('\t')?;
+        let years = vin_year_candidates(vin, reference_year(reference)?);
+
+        Some(array(years.iter().map(|y| y.to_string())))
+    }));
+
+    Ok(())
+}
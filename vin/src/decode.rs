@@ -0,0 +1,306 @@
+//! Full VIN decoding into a single structured record.
+//!
+//! The individual functions in [`crate::vin`] each clean their input again, so
+//! decoding every field of a VIN through them repeats the regex scan several
+//! times per row. [`vin_info`] cleans the VIN once and fills all fields from
+//! the cleaned value, and [`vin_decode`] serializes the result as JSON for use
+//! as a ClickHouse UDF.
+//!
+//! # VIN Sections
+//!
+//! - Characters 1-3: World Manufacturer Identifier (WMI)
+//! - Characters 4-9: Vehicle Descriptor Section (VDS), including the check digit
+//! - Characters 10-17: Vehicle Identifier Section (VIS)
+//!
+//! # Examples
+//!
+//! ```
+//! use vin::decode::vin_info;
+//!
+//! let info = vin_info("1GKKRNED9EJ262581").unwrap();
+//! assert_eq!(info.wmi, "1GK");
+//! assert_eq!(info.manufacturer.as_deref(), Some("General Motors USA"));
+//! assert_eq!(info.model_year, Some(2014));
+//! assert_eq!(info.confidence, "high");
+//! ```
+
+use serde::Serialize;
+
+use crate::vds::vds_registry;
+use crate::vin::{
+    check_digit_required_of, check_digit_valid_of, continent_of, country_of, vin_cleaner, wmi_of,
+    year_of,
+};
+use crate::wmi_db::wmi_database;
+
+/// All information that can be decoded from a VIN.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VinInfo {
+    /// The cleaned VIN.
+    pub vin: String,
+    /// World Manufacturer Identifier (3 characters, or 6 for small manufacturers).
+    pub wmi: String,
+    /// Vehicle Descriptor Section (characters 4-9).
+    pub vds: String,
+    /// Vehicle Identifier Section (characters 10-17).
+    pub vis: String,
+    /// Manufacturer name from the WMI table.
+    pub manufacturer: Option<String>,
+    /// Brand of the manufacturer.
+    pub brand: Option<String>,
+    /// Parent group of the manufacturer.
+    pub group: Option<String>,
+    /// Model line, decoded from the VDS (see [`crate::vds`]).
+    pub model: Option<String>,
+    /// Body style, decoded from the VDS.
+    pub body_style: Option<String>,
+    /// Engine, decoded from the VDS.
+    pub engine: Option<String>,
+    /// Continent of origin.
+    pub continent: Option<&'static str>,
+    /// Country of origin.
+    pub country: Option<&'static str>,
+    /// ISO 3166 alpha-2 code of the country of origin.
+    pub country_code: Option<&'static str>,
+    /// Decoded model year.
+    pub model_year: Option<i32>,
+    /// Assembly plant code (character 11).
+    pub plant_code: Option<String>,
+    /// Production serial number.
+    pub serial_number: String,
+    /// Whether the check digit (character 9) matches the computed one.
+    pub check_digit_valid: bool,
+    /// Whether the check digit is mandatory in the VIN's region.
+    pub check_digit_required: bool,
+    /// How much the decoded fields can be trusted: `high`, `medium` or `low`.
+    pub confidence: &'static str,
+}
+
+/// Decodes every field of a VIN, cleaning the input only once.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (can be dirty/unformatted, will be cleaned)
+///
+/// # Returns
+///
+/// * `Some(VinInfo)` - The decoded record
+/// * `None` - If no VIN could be found in the input
+///
+/// # Confidence
+///
+/// - `high`: 17 characters, known manufacturer, decoded model year and a valid check digit
+/// - `medium`: 17 characters and a known manufacturer, but the check digit does not
+///   verify (usually because it is not mandatory in the VIN's region)
+/// - `low`: anything else (16 or 18 characters, unknown manufacturer)
+///
+/// # Small Manufacturers
+///
+/// When the 3rd character is `9`, characters 12-14 belong to the WMI, so the
+/// serial number only covers characters 15-17.
+///
+/// # Examples
+///
+/// ```
+/// use vin::decode::vin_info;
+///
+/// let info = vin_info("VIN: wf05xxgcc5fj00000").unwrap();
+/// assert_eq!(info.vin, "WF05XXGCC5FJ00000");
+/// assert_eq!(info.vds, "5XXGCC");
+/// assert_eq!(info.vis, "5FJ00000");
+/// assert_eq!(info.country, Some("Germany"));
+/// assert_eq!(info.plant_code.as_deref(), Some("F"));
+/// assert_eq!(info.serial_number, "J00000");
+///
+/// assert!(vin_info("invalid").is_none());
+/// ```
+pub fn vin_info(vin: &str) -> Option<VinInfo> {
+    let vin = vin_cleaner(vin)?;
+
+    let wmi = wmi_of(&vin).unwrap_or_else(|| vin.chars().take(3).collect());
+    let entry = wmi_database().entry(&wmi);
+    let manufacturer = entry.map(|e| e.manufacturer.clone());
+    let model_year = year_of(&vin);
+    let check_digit_valid = check_digit_valid_of(&vin);
+
+    let country = country_of(&vin);
+    let serial_start = if wmi.len() == 6 { 14 } else { 11 };
+    let vds = match wmi.len() {
+        6 => None,
+        _ => vds_registry().decode(&vin, model_year),
+    }
+    .unwrap_or_default();
+
+    let confidence = match (vin.len() == 17, manufacturer.is_some()) {
+        (true, true) if model_year.is_some() && check_digit_valid => "high",
+        (true, true) => "medium",
+        _ => "low",
+    };
+
+    Some(VinInfo {
+        wmi,
+        vds: section(&vin, 3, 9),
+        vis: section(&vin, 9, vin.len()),
+        manufacturer,
+        brand: entry.and_then(|e| e.brand.clone()),
+        group: entry.and_then(|e| e.group.clone()),
+        model: vds.model,
+        body_style: vds.body_style,
+        engine: vds.engine,
+        continent: continent_of(&vin),
+        country: country.map(|(_, name)| name),
+        country_code: country.map(|(code, _)| code),
+        model_year,
+        plant_code: vin.get(10..11).map(str::to_string),
+        serial_number: section(&vin, serial_start, vin.len()),
+        check_digit_valid,
+        check_digit_required: check_digit_required_of(&vin),
+        confidence,
+        vin,
+    })
+}
+
+/// Decodes a VIN into a JSON object.
+///
+/// This is the UDF entry point for `vinDecode`. The fields are the ones of
+/// [`VinInfo`]; fields that cannot be decoded are `null`.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (can be dirty/unformatted, will be cleaned)
+///
+/// # Returns
+///
+/// * `Some(String)` - A JSON object with all decoded fields
+/// * `None` - If no VIN could be found in the input
+///
+/// # Examples
+///
+/// ```
+/// use vin::decode::vin_decode;
+///
+/// let json = vin_decode("1GKKRNED9EJ262581").unwrap();
+/// assert!(json.starts_with(r#"{"vin":"1GKKRNED9EJ262581","wmi":"1GK""#));
+/// assert!(json.contains(r#""model_year":2014"#));
+///
+/// assert!(vin_decode("").is_none());
+/// ```
+pub fn vin_decode(vin: &str) -> Option<String> {
+    vin_info(vin).and_then(|info| serde_json::to_string(&info).ok())
+}
+
+/// Returns the characters `start..end` of an ASCII VIN, or an empty string if out of range.
+fn section(vin: &str, start: usize, end: usize) -> String {
+    vin.get(start..end).unwrap_or_default().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vin_info() {
+        let info = vin_info("1GKKRNED9EJ262581").unwrap();
+        assert_eq!(
+            info,
+            VinInfo {
+                vin: "1GKKRNED9EJ262581".to_string(),
+                wmi: "1GK".to_string(),
+                vds: "KRNED9".to_string(),
+                vis: "EJ262581".to_string(),
+                manufacturer: Some("General Motors USA".to_string()),
+                brand: None,
+                group: Some("General Motors".to_string()),
+                model: None,
+                body_style: None,
+                engine: Some("3.6L V6 (LLT)".to_string()),
+                continent: Some("North America"),
+                country: Some("United States"),
+                country_code: Some("US"),
+                model_year: Some(2014),
+                plant_code: Some("J".to_string()),
+                serial_number: "262581".to_string(),
+                check_digit_valid: true,
+                check_digit_required: true,
+                confidence: "high",
+            }
+        );
+    }
+
+    #[test]
+    fn test_vin_info_matches_single_functions() {
+        let vinl = [
+            "1GKKRNED9EJ262581",
+            "2A4GP54L16R805929",
+            "JM1BL1M72C1587426",
+            "3VWDX7AJ2BM339496",
+            "KNMCSHLMS6P600875",
+            "TRUZZZSNZY1063363",
+            " 1GKKRNED9EJ262581 (ok) ",
+        ];
+
+        for v in vinl {
+            let info = vin_info(v).unwrap();
+            assert_eq!(info.manufacturer, crate::vin::vin_manuf(v), "{}", v);
+            assert_eq!(
+                info.model_year.map(|y| y.to_string()),
+                crate::vin::vin_year(v),
+                "{}",
+                v
+            );
+            assert_eq!(info.continent, crate::vin::vin_continent(v), "{}", v);
+        }
+    }
+
+    #[test]
+    fn test_vin_info_confidence() {
+        // Valid check digit and known manufacturer
+        assert_eq!(vin_info("JTDKN3DU9F0421684").unwrap().confidence, "high");
+        // Known manufacturer, check digit not used (Europe)
+        assert_eq!(vin_info("WF05XXGCC5FJ00000").unwrap().confidence, "medium");
+        // 16 characters
+        assert_eq!(vin_info("G1ND52F14M700000").unwrap().confidence, "low");
+    }
+
+    #[test]
+    fn test_vin_info_small_manufacturer() {
+        let info = vin_info("VF9795AB1CD123456").unwrap();
+        assert_eq!(info.wmi, "VF9123");
+        assert_eq!(info.serial_number, "456");
+    }
+
+    #[test]
+    fn test_vin_info_country() {
+        assert_eq!(
+            vin_info("SALGA2HF0EA000000").unwrap().country,
+            Some("United Kingdom")
+        );
+        assert_eq!(
+            vin_info("3VWDX7AJ2BM339496").unwrap().country,
+            Some("Mexico")
+        );
+        assert_eq!(
+            vin_info("Y7A6135B660801530").unwrap().country,
+            Some("Ukraine")
+        );
+    }
+
+    #[test]
+    fn test_vin_info_vds() {
+        let info = vin_info("1FTEW1CM9BFA74557").unwrap();
+        assert_eq!(info.model.as_deref(), Some("F-150"));
+        assert_eq!(info.body_style.as_deref(), Some("SuperCrew"));
+        assert_eq!(info.engine.as_deref(), Some("3.7L V6"));
+
+        // No decoder for this manufacturer
+        let info = vin_info("KNMCSHLMS6P600875").unwrap();
+        assert_eq!(info.model, None);
+    }
+
+    #[test]
+    fn test_vin_decode_bad_input() {
+        assert!(vin_decode("").is_none());
+        assert!(vin_decode("123").is_none());
+        assert!(vin_decode("...............").is_none());
+    }
+}
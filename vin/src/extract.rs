@@ -0,0 +1,225 @@
+//! Extraction of every VIN contained in a free text.
+//!
+//! [`crate::vin::vin_cleaner`] stops at the first regex match, which is enough for
+//! a column holding one VIN but loses the rest of a listing description or a
+//! service note mentioning several vehicles. [`vin_extract_all`] scans the whole
+//! text and returns each validated VIN once, with its byte offset.
+//!
+//! # Separators
+//!
+//! VINs are often written in groups, e.g. `1GK-KRNED9-EJ262581` or
+//! `WF0 5XXGCC 5FJ00000`. Runs of spaces and dashes between letters and digits
+//! are skipped, so a VIN may span several groups as long as it starts at the
+//! beginning of a group and ends at the end of one.
+//!
+//! # Examples
+//!
+//! ```
+//! use vin::extract::vin_extract_all;
+//!
+//! let vins = vin_extract_all("Trade-in 1GKKRNED9EJ262581, new WF0-5XXGCC-5FJ00000");
+//! assert_eq!(
+//!     vins,
+//!     vec![
+//!         ("1GKKRNED9EJ262581".to_string(), 9),
+//!         ("WF05XXGCC5FJ00000".to_string(), 32),
+//!     ]
+//! );
+//! ```
+
+use crate::vin::{check_digit_required_of, check_digit_valid_of, is_valid_of};
+
+const VIN_LEN: usize = 17;
+
+/// One alphanumeric character of the input, uppercased.
+struct Char {
+    c: char,
+    /// Byte offset of the character in the original text.
+    offset: usize,
+    /// `true` if the character is the first one after a separator.
+    group_start: bool,
+}
+
+/// Extracts all distinct, validated VINs from a text.
+///
+/// Candidates are the 17-character windows of a block of letters and digits, where
+/// a block may contain spaces and dashes between groups of characters. A candidate
+/// is kept when it passes the rules of [`crate::vin::vin_is_valid`] and either:
+///
+/// - starts at the beginning of a group and ends at the end of one, or
+/// - lies within a single group and has a mandatory, valid check digit, so that a
+///   VIN glued to other characters (`VIN1GKKRNED9EJ262581`) is still found
+///
+/// When the check digit is not mandatory, the last 4 characters must also be
+/// digits, which excludes most words and part numbers.
+///
+/// Only 17-character VINs can be validated, so the 16 and 18-character fallbacks
+/// of [`crate::vin::vin_cleaner`] are never returned. Characters of an accepted
+/// VIN are not reused by another candidate, so there are no overlapping matches.
+///
+/// # Arguments
+///
+/// * `text` - Any text that may contain VINs
+///
+/// # Returns
+///
+/// The uppercase VINs in order of first appearance, each with the byte offset
+/// (0-based) of its first character in `text`. Repeated VINs are only returned once.
+///
+/// # Examples
+///
+/// ```
+/// use vin::extract::vin_extract_all;
+///
+/// // Several VINs, one repeated
+/// let vins = vin_extract_all("1gkkrned9ej262581 / JTDKN3DU9F0421684 / 1GKKRNED9EJ262581");
+/// assert_eq!(vins.len(), 2);
+/// assert_eq!(vins[1], ("JTDKN3DU9F0421684".to_string(), 20));
+///
+/// // Wrong check digit for a North American VIN
+/// assert!(vin_extract_all("1GKKRNED8EJ262581").is_empty());
+///
+/// assert!(vin_extract_all("").is_empty());
+/// ```
+pub fn vin_extract_all(text: &str) -> Vec<(String, usize)> {
+    let mut vins: Vec<(String, usize)> = Vec::new();
+
+    for block in blocks(text) {
+        let mut i = 0;
+
+        while i + VIN_LEN <= block.len() {
+            let window = &block[i..i + VIN_LEN];
+            let candidate: String = window.iter().map(|ch| ch.c).collect();
+            let next_group_start = block.get(i + VIN_LEN).is_none_or(|ch| ch.group_start);
+
+            let aligned = window[0].group_start && next_group_start;
+            let single_group = window[1..].iter().all(|ch| !ch.group_start);
+            let accepted = is_valid_of(&candidate)
+                && (aligned
+                    || single_group
+                        && check_digit_required_of(&candidate)
+                        && check_digit_valid_of(&candidate));
+
+            if accepted {
+                if !vins.iter().any(|(vin, _)| *vin == candidate) {
+                    vins.push((candidate, window[0].offset));
+                }
+                i += VIN_LEN;
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    vins
+}
+
+/// Splits a text into blocks of ASCII letters and digits, joined by spaces and dashes.
+fn blocks(text: &str) -> Vec<Vec<Char>> {
+    let mut blocks = Vec::new();
+    let mut block: Vec<Char> = Vec::new();
+    let mut after_separator = true;
+
+    for (offset, c) in text.char_indices() {
+        if c.is_ascii_alphanumeric() {
+            block.push(Char {
+                c: c.to_ascii_uppercase(),
+                offset,
+                group_start: after_separator,
+            });
+            after_separator = false;
+        } else if c == ' ' || c == '-' {
+            after_separator = true;
+        } else {
+            if block.len() >= VIN_LEN {
+                blocks.push(block);
+            }
+            block = Vec::new();
+            after_separator = true;
+        }
+    }
+
+    if block.len() >= VIN_LEN {
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vins(text: &str) -> Vec<String> {
+        vin_extract_all(text)
+            .into_iter()
+            .map(|(vin, _)| vin)
+            .collect()
+    }
+
+    #[test]
+    fn test_vin_extract_all() {
+        assert_eq!(
+            vins("VINs: 1GKKRNED9EJ262581, 2A4GP54L16R805929 and JM1BL1M72C1587426."),
+            vec![
+                "1GKKRNED9EJ262581",
+                "2A4GP54L16R805929",
+                "JM1BL1M72C1587426"
+            ]
+        );
+        assert_eq!(
+            vins("1GKKRNED9EJ262581\n1GKKRNED9EJ262581"),
+            vec!["1GKKRNED9EJ262581"]
+        );
+        assert!(vins("no vin here").is_empty());
+    }
+
+    #[test]
+    fn test_vin_extract_all_separators() {
+        assert_eq!(vins("1GK-KRNED9-EJ262581"), vec!["1GKKRNED9EJ262581"]);
+        assert_eq!(vins("1GK KRN ED9 EJ2 625 81"), vec!["1GKKRNED9EJ262581"]);
+        assert_eq!(
+            vins("sold TRUZZZSNZY1063363 WF05XXGCC5FJ00000 today"),
+            vec!["TRUZZZSNZY1063363", "WF05XXGCC5FJ00000"]
+        );
+        // Not aligned with the groups and no check digit to confirm it
+        assert!(vins("WF0 5XXGCC5FJ000001").is_empty());
+    }
+
+    #[test]
+    fn test_vin_extract_all_glued() {
+        assert_eq!(vins("VIN1GKKRNED9EJ262581X"), vec!["1GKKRNED9EJ262581"]);
+        assert_eq!(
+            vins("1GKKRNED9EJ2625812A4GP54L16R805929"),
+            vec!["1GKKRNED9EJ262581", "2A4GP54L16R805929"]
+        );
+    }
+
+    #[test]
+    fn test_vin_extract_all_no_overlap() {
+        // The 18-character pattern of vin_cleaner must not produce a second match
+        assert_eq!(
+            vin_extract_all("x 1GKKRNED9EJ2625811"),
+            vec![("1GKKRNED9EJ262581".to_string(), 2)]
+        );
+        assert_eq!(
+            vin_extract_all("x 1GKKRNED9EJ262581 1"),
+            vec![("1GKKRNED9EJ262581".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_vin_extract_all_small_manufacturer() {
+        // Position 14 may be a letter when position 3 is 9
+        assert_eq!(
+            vin_extract_all("x WA9ZZZ12345ABC123 y"),
+            vec![("WA9ZZZ12345ABC123".to_string(), 2)]
+        );
+    }
+
+    #[test]
+    fn test_vin_extract_all_offsets() {
+        let vins = vin_extract_all("« 1GKKRNED9EJ262581 »");
+        assert_eq!(vins, vec![("1GKKRNED9EJ262581".to_string(), 3)]);
+    }
+}
@@ -0,0 +1,517 @@
+//! Generation of realistic random VINs for test data and benchmarks.
+//!
+//! [`VinGenerator`] produces VINs for a given WMI or region and model year range.
+//! Generated VINs have a valid check digit and decode back to their WMI and model
+//! year with the functions of [`crate::vin`]. Since [`crate::vin::vin_year_at`] decodes
+//! years relative to a reference year, the model years are limited to the ones it
+//! reads back: from 1980 in North America, where position 7 fixes the 30-year
+//! cycle, and from 28 years before the reference year elsewhere, up to the year
+//! after it and at most 2039. The UDF uses the current year, like [`crate::vin::vin_year`]. Optional
+//! corruptions reproduce the defects found in real data, to load-test pipelines
+//! and fuzz [`crate::vin::vin_cleaner`].
+//!
+//! Generation is seeded, so the same seed always gives the same VIN. In ClickHouse,
+//! pass the row number as seed:
+//!
+//! ```sql
+//! SELECT vinGenerate(number, 'Europe', '2015-2020') FROM numbers(1000000)
+//! ```
+//!
+//! # Examples
+//!
+//! ```
+//! use vin::generate::VinGenerator;
+//! use vin::{vin_is_valid, wmi};
+//!
+//! let generator = VinGenerator::new("1GK", 2014..=2014, 2024).unwrap();
+//! let vin = generator.generate_seeded(42);
+//! assert_eq!(wmi(&vin).unwrap(), "1GK");
+//! assert_eq!(vin_is_valid(&vin).unwrap(), "true");
+//! assert_eq!(generator.generate_seeded(42), vin);
+//! ```
+
+use anyhow::{bail, Result};
+use chrono::{Datelike, Local};
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+use crate::vin::{check_digit_of, continent_of, get_wmicsv};
+
+/// Characters allowed in a VIN (everything but I, O and Q).
+const VIN_CHARS: &[u8] = b"ABCDEFGHJKLMNPRSTUVWXYZ0123456789";
+
+/// Model year codes, starting with 1980 and repeating every 30 years.
+const YEAR_CHARS: &[u8] = b"ABCDEFGHJKLMNPRSTVWXY123456789";
+
+/// First model year of the 1980-2009 cycle of year codes.
+const FIRST_YEAR: i32 = 1980;
+
+/// Last model year of the 2010-2039 cycle, the latest that [`crate::vin`] decodes.
+const LAST_YEAR: i32 = 2039;
+
+/// First model year of the default range of [`vin_generate`].
+const DEFAULT_FIRST_YEAR: i32 = 2010;
+
+/// A defect applied to a generated VIN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Corruption {
+    /// Some letters are lowercased.
+    Case,
+    /// Spaces are added before and after the VIN.
+    Whitespace,
+    /// One `1` or `0` is replaced with `I`, `O` or `Q`.
+    Ioq,
+    /// The last one or two characters are removed.
+    Truncate,
+}
+
+impl FromStr for Corruption {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "case" => Ok(Corruption::Case),
+            "whitespace" => Ok(Corruption::Whitespace),
+            "ioq" => Ok(Corruption::Ioq),
+            "truncate" => Ok(Corruption::Truncate),
+            _ => bail!("Unknown corruption: {}", s),
+        }
+    }
+}
+
+/// Generator of random VINs for a WMI or region and a model year range.
+#[derive(Debug, Clone)]
+pub struct VinGenerator {
+    wmis: Vec<String>,
+    years: RangeInclusive<i32>,
+    corruptions: Vec<Corruption>,
+    corruption_rate: f64,
+}
+
+impl VinGenerator {
+    /// Creates a generator of valid VINs.
+    ///
+    /// # Arguments
+    ///
+    /// * `wmi_or_region` - A 3 or 6-character WMI, or a region: a continent
+    ///   (`North America`, `Europe`, `Asia`, ...), `NA`, `EU`, or an empty string
+    ///   for any WMI of the embedded table
+    /// * `years` - Model years to pick from, that [`crate::vin::vin_year_at`] decodes
+    ///   back at `reference_year` (see the [module documentation](self))
+    /// * `reference_year` - The latest plausible model year minus 1, usually the
+    ///   current year
+    ///
+    /// # Errors
+    ///
+    /// Fails if the region is unknown, the WMI contains invalid characters, or the
+    /// years are out of range.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vin::generate::VinGenerator;
+    /// use vin::vin_continent;
+    ///
+    /// let generator = VinGenerator::new("europe", 2015..=2020, 2024).unwrap();
+    /// let vin = generator.generate_seeded(7);
+    /// assert_eq!(vin_continent(&vin).unwrap(), "Europe");
+    ///
+    /// assert!(VinGenerator::new("Atlantis", 2015..=2020, 2024).is_err());
+    /// assert!(VinGenerator::new("1GK", 1950..=1960, 2024).is_err());
+    /// assert!(VinGenerator::new("1GK", 1985..=1990, 2024).is_ok());
+    /// assert!(VinGenerator::new("Europe", 1985..=1990, 2024).is_err());
+    /// assert!(VinGenerator::new("Europe", 1985..=1990, 2010).is_ok());
+    /// ```
+    pub fn new(
+        wmi_or_region: &str,
+        years: RangeInclusive<i32>,
+        reference_year: i32,
+    ) -> Result<Self> {
+        let wmis = wmis_of(wmi_or_region)?;
+
+        let first_year = match wmis
+            .iter()
+            .all(|wmi| continent_of(wmi) == Some("North America"))
+        {
+            true => FIRST_YEAR,
+            false => FIRST_YEAR.max(reference_year - 28),
+        };
+        let supported = first_year..=LAST_YEAR.min(reference_year + 1);
+        if years.is_empty()
+            || !supported.contains(years.start())
+            || !supported.contains(years.end())
+        {
+            bail!(
+                "Model years for {:?} must be between {} and {}",
+                wmi_or_region,
+                supported.start(),
+                supported.end()
+            );
+        }
+
+        Ok(VinGenerator {
+            wmis,
+            years,
+            corruptions: Vec::new(),
+            corruption_rate: 0.0,
+        })
+    }
+
+    /// Corrupts generated VINs with one of `corruptions`, picked at random.
+    ///
+    /// # Arguments
+    ///
+    /// * `corruptions` - The defects to apply
+    /// * `rate` - Share of the VINs to corrupt, between 0 and 1
+    pub fn with_corruptions(mut self, corruptions: &[Corruption], rate: f64) -> Self {
+        self.corruptions = corruptions.to_vec();
+        self.corruption_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Generates a VIN with the given random number generator.
+    pub fn generate<R: Rng>(&self, rng: &mut R) -> String {
+        let wmi = self.wmis.choose(rng).expect("WMI list is never empty");
+        let year = rng.random_range(self.years.clone());
+        let mut vin = [0u8; 17];
+
+        for c in vin.iter_mut() {
+            *c = *VIN_CHARS.choose(rng).expect("VIN_CHARS is not empty");
+        }
+        for c in vin[11..].iter_mut() {
+            *c = b'0' + rng.random_range(0..10);
+        }
+
+        vin[..3].copy_from_slice(&wmi.as_bytes()[..3]);
+        if wmi.len() == 6 {
+            vin[11..14].copy_from_slice(&wmi.as_bytes()[3..]);
+        }
+
+        let year_char = YEAR_CHARS[((year - FIRST_YEAR) % 30) as usize];
+        match continent_of(wmi) {
+            Some("North America") => {
+                // Position 7 tells the 1980-2009 and 2010-2039 cycles apart
+                let chars: &[u8] = if year >= 2010 {
+                    b"ABCDEFGHJKLMNPRSTUVWXYZ"
+                } else {
+                    b"0123456789"
+                };
+                vin[6] = *chars.choose(rng).expect("chars is not empty");
+                vin[9] = year_char;
+            }
+            // European model years are read from position 11 by crate::vin
+            Some("Europe") => vin[10] = year_char,
+            _ => vin[9] = year_char,
+        }
+
+        let mut vin = String::from_utf8(vin.to_vec()).expect("VIN is ASCII");
+        let check_digit = check_digit_of(&vin).expect("VIN has 17 valid characters");
+        vin.replace_range(8..9, check_digit.encode_utf8(&mut [0; 4]));
+
+        if !self.corruptions.is_empty() && rng.random_bool(self.corruption_rate) {
+            let corruption = *self
+                .corruptions
+                .choose(rng)
+                .expect("corruptions is not empty");
+            vin = corrupt(&vin, corruption, rng);
+        }
+
+        vin
+    }
+
+    /// Generates the VIN of a seed.
+    pub fn generate_seeded(&self, seed: u64) -> String {
+        self.generate(&mut StdRng::seed_from_u64(seed))
+    }
+}
+
+/// Generates a VIN from a tab-separated seed, WMI or region, and model years.
+///
+/// This is the UDF entry point for `vinGenerate`.
+///
+/// # Arguments
+///
+/// * `input` - `seed`, `wmi_or_region` and `years` separated by tabs, where
+///   `years` is a single year (`2015`) or a range (`2010-2020`). The years
+///   default to 2010 through the current year when omitted or empty, and must
+///   decode back with [`crate::vin::vin_year`].
+///
+/// # Returns
+///
+/// * `Some(String)` - The generated VIN
+/// * `None` - If the input cannot be parsed (see [`VinGenerator::new`])
+///
+/// # Examples
+///
+/// ```
+/// use vin::generate::vin_generate;
+/// use vin::vin_year_at;
+///
+/// let vin = vin_generate("1\tNA\t2015").unwrap();
+/// assert_eq!(vin_year_at(&vin, 2024).unwrap(), "2015");
+/// assert_eq!(vin_generate("1\tNA\t2015"), Some(vin));
+///
+/// assert!(vin_generate("not a seed\tNA").is_none());
+/// ```
+pub fn vin_generate(input: &str) -> Option<String> {
+    vin_generate_with(input, Local::now().year(), &[], 0.0)
+}
+
+/// Same as [`vin_generate`] at a reference year, corrupting a share of the VINs.
+///
+/// # Arguments
+///
+/// * `input` - See [`vin_generate`], the default years end at `reference_year`
+/// * `reference_year` - See [`VinGenerator::new`]
+/// * `corruptions` - The defects to apply
+/// * `rate` - Share of the VINs to corrupt, between 0 and 1
+pub fn vin_generate_with(
+    input: &str,
+    reference_year: i32,
+    corruptions: &[Corruption],
+    rate: f64,
+) -> Option<String> {
+    let mut fields = input.split('\t');
+    let seed: u64 = fields.next()?.trim().parse().ok()?;
+    let wmi_or_region = fields.next().unwrap_or_default();
+    let years = match fields.next().map(str::trim) {
+        None | Some("") => DEFAULT_FIRST_YEAR..=reference_year,
+        Some(years) => years_of(years)?,
+    };
+
+    let generator = VinGenerator::new(wmi_or_region, years, reference_year)
+        .map_err(|e| eprintln!("ERROR: {:#}", e))
+        .ok()?
+        .with_corruptions(corruptions, rate);
+
+    Some(generator.generate_seeded(seed))
+}
+
+/// Parses `2015` or `2010-2020`.
+fn years_of(s: &str) -> Option<RangeInclusive<i32>> {
+    match s.split_once('-') {
+        Some((from, to)) => Some(from.trim().parse().ok()?..=to.trim().parse().ok()?),
+        None => {
+            let year = s.parse().ok()?;
+            Some(year..=year)
+        }
+    }
+}
+
+/// Returns the WMIs to generate VINs for.
+fn wmis_of(wmi_or_region: &str) -> Result<Vec<String>> {
+    let wmi_or_region = wmi_or_region.trim();
+    let is_wmi = matches!(wmi_or_region.len(), 3 | 6)
+        && wmi_or_region.bytes().all(|c| VIN_CHARS.contains(&c));
+    if is_wmi {
+        return Ok(vec![wmi_or_region.to_string()]);
+    }
+
+    let region = wmi_or_region.to_lowercase().replace(['-', '_'], " ");
+    let continent = match region.as_str() {
+        "" => None,
+        "na" => Some("North America"),
+        "eu" => Some("Europe"),
+        "sa" => Some("South America"),
+        region => [
+            "Africa",
+            "Asia",
+            "Europe",
+            "North America",
+            "Oceania",
+            "South America",
+        ]
+        .into_iter()
+        .find(|continent| continent.to_lowercase() == region)
+        .map(Some)
+        .ok_or_else(|| anyhow::anyhow!("Unknown WMI or region: {}", wmi_or_region))?,
+    };
+
+    let mut wmis: Vec<String> = get_wmicsv()
+        .keys()
+        .filter(|wmi| wmi.len() == 3 && wmi.as_bytes()[2] != b'9')
+        .filter(|wmi| wmi.bytes().all(|c| VIN_CHARS.contains(&c)))
+        .filter(|wmi| continent.is_none() || continent_of(wmi) == continent)
+        .cloned()
+        .collect();
+    // Sorted so that a seed gives the same VIN on every run
+    wmis.sort();
+
+    if wmis.is_empty() {
+        bail!("No WMI found for region: {}", wmi_or_region);
+    }
+    Ok(wmis)
+}
+
+/// Applies a corruption to a valid VIN.
+fn corrupt<R: Rng>(vin: &str, corruption: Corruption, rng: &mut R) -> String {
+    match corruption {
+        Corruption::Case => vin
+            .chars()
+            .map(|c| match rng.random_bool(0.5) {
+                true => c.to_ascii_lowercase(),
+                false => c,
+            })
+            .collect(),
+        Corruption::Whitespace => format!(
+            "{}{}{}",
+            " ".repeat(rng.random_range(1..=3)),
+            vin,
+            " ".repeat(rng.random_range(0..=3))
+        ),
+        Corruption::Ioq => {
+            let mut chars: Vec<char> = vin.chars().collect();
+            let positions: Vec<usize> = (0..chars.len())
+                .filter(|&i| matches!(chars[i], '0' | '1'))
+                .collect();
+            let i = match positions.choose(rng) {
+                Some(&i) => i,
+                None => rng.random_range(0..chars.len()),
+            };
+            chars[i] = match chars[i] {
+                '1' => 'I',
+                '0' => *['O', 'Q'].choose(rng).expect("not empty"),
+                _ => *['I', 'O', 'Q'].choose(rng).expect("not empty"),
+            };
+            chars.into_iter().collect()
+        }
+        Corruption::Truncate => vin[..17 - rng.random_range(1..=2)].to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vin::{is_valid_of, vin_cleaner, vin_year, vin_year_at, wmi};
+
+    const REFERENCE_YEAR: i32 = 2024;
+
+    #[test]
+    fn test_vin_generate_round_trip() {
+        for region in ["NA", "Europe", "Asia", ""] {
+            let generator = VinGenerator::new(region, 2010..=2024, REFERENCE_YEAR).unwrap();
+            for seed in 0..500 {
+                let vin = generator.generate_seeded(seed);
+                assert!(is_valid_of(&vin), "{}", vin);
+                assert_eq!(vin_cleaner(&vin).as_deref(), Some(vin.as_str()));
+                let year: i32 = vin_year_at(&vin, REFERENCE_YEAR).unwrap().parse().unwrap();
+                assert!((2010..=2024).contains(&year), "{} {}", vin, year);
+            }
+        }
+    }
+
+    #[test]
+    fn test_vin_generate_default_years_round_trip() {
+        for reference_year in [REFERENCE_YEAR, 2037] {
+            for region in ["NA", "Europe", "Asia", ""] {
+                for seed in 0..500 {
+                    let input = format!("{}\t{}", seed, region);
+                    let vin = vin_generate_with(&input, reference_year, &[], 0.0).unwrap();
+                    let year: i32 = vin_year_at(&vin, reference_year).unwrap().parse().unwrap();
+                    assert!(
+                        (DEFAULT_FIRST_YEAR..=reference_year).contains(&year),
+                        "{} {}",
+                        vin,
+                        year
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_vin_generate_years_round_trip() {
+        for reference_year in [REFERENCE_YEAR, 2038] {
+            let next_year = reference_year + 1;
+            for (region, years) in [
+                ("NA", 1980..=next_year),
+                ("Europe", next_year - 29..=next_year),
+                ("", next_year - 29..=next_year),
+            ] {
+                let generator = VinGenerator::new(region, years.clone(), reference_year).unwrap();
+                for seed in 0..500 {
+                    let vin = generator.generate_seeded(seed);
+                    let year: i32 = vin_year_at(&vin, reference_year).unwrap().parse().unwrap();
+                    assert!(years.contains(&year), "{} {}", vin, year);
+                }
+            }
+        }
+
+        // Years that vin_year_at cannot decode back
+        let next_year = REFERENCE_YEAR + 1;
+        assert!(VinGenerator::new("NA", next_year..=next_year + 1, REFERENCE_YEAR).is_err());
+        assert!(VinGenerator::new("Europe", next_year - 30..=next_year, REFERENCE_YEAR).is_err());
+        assert!(VinGenerator::new("", 1990..=2000, REFERENCE_YEAR).is_err());
+        assert!(VinGenerator::new("Europe", 2010..=2024, 2039).is_err());
+        assert!(VinGenerator::new("NA", 2030..=2040, 2039).is_err());
+    }
+
+    #[test]
+    fn test_vin_generate_north_america_cycles() {
+        let generator = VinGenerator::new("1GK", 1985..=2005, REFERENCE_YEAR).unwrap();
+        for seed in 0..200 {
+            let vin = generator.generate_seeded(seed);
+            let year: i32 = vin_year_at(&vin, REFERENCE_YEAR).unwrap().parse().unwrap();
+            assert!((1985..=2005).contains(&year), "{} {}", vin, year);
+            assert!(vin[13..].chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn test_vin_generate_wmi() {
+        let vin = VinGenerator::new("WA9ZZZ", 2015..=2015, REFERENCE_YEAR)
+            .unwrap()
+            .generate_seeded(3);
+        assert_eq!(wmi(&vin).unwrap(), "WA9ZZZ");
+        assert_eq!(&vin[11..14], "ZZZ");
+    }
+
+    #[test]
+    fn test_vin_generate_input() {
+        let generate = |input| vin_generate_with(input, REFERENCE_YEAR, &[], 0.0);
+        assert!(generate("5\t1GK\t2010-2020").is_some());
+        assert!(generate("5\t1GK").is_some());
+        assert!(generate("5").is_some());
+        assert!(generate("5\tMars").is_none());
+        assert!(generate("5\t1GK\t2020-2010").is_none());
+        assert!(generate("").is_none());
+        assert!(vin_generate("5\t1GK").is_some());
+    }
+
+    #[test]
+    fn test_vin_generate_corruptions() {
+        let generator = VinGenerator::new("NA", 2010..=2024, REFERENCE_YEAR).unwrap();
+        for corruption in [
+            Corruption::Case,
+            Corruption::Whitespace,
+            Corruption::Ioq,
+            Corruption::Truncate,
+        ] {
+            let corrupted = generator.clone().with_corruptions(&[corruption], 1.0);
+            for seed in 0..200 {
+                let vin = corrupted.generate_seeded(seed);
+                match corruption {
+                    Corruption::Case | Corruption::Whitespace => {
+                        let clean = generator.generate_seeded(seed);
+                        assert_eq!(vin.trim().to_uppercase(), clean);
+                        assert_eq!(vin_cleaner(&vin), Some(clean));
+                    }
+                    Corruption::Ioq => assert!(vin.contains(['I', 'O', 'Q'])),
+                    Corruption::Truncate => assert!((15..=16).contains(&vin.len())),
+                }
+                // Corrupted input must never make the parsers panic
+                let _ = vin_year(&vin);
+                let _ = wmi(&vin);
+            }
+        }
+    }
+
+    #[test]
+    fn test_corruption_from_str() {
+        assert_eq!("IOQ".parse::<Corruption>().unwrap(), Corruption::Ioq);
+        assert!("typo".parse::<Corruption>().is_err());
+    }
+}
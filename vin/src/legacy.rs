@@ -0,0 +1,550 @@
+//! Classification and decoding of pre-1981 chassis numbers.
+//!
+//! The 17-character VIN became mandatory with the 1981 model year. Older
+//! vehicles carry manufacturer-specific chassis numbers, usually 9 to 13
+//! characters long, which the functions of [`crate::vin`] do not accept.
+//! [`vin_classify`] tells modern VINs, legacy chassis numbers and invalid input
+//! apart, and [`vin_legacy_info`] decodes the formats of major manufacturers.
+//!
+//! Only numbers in one of the supported formats are classified as legacy. Other
+//! numbers of the same length are `unknown`, since order numbers, invoice IDs and
+//! phone numbers cannot be told apart from chassis numbers of other manufacturers.
+//!
+//! # Supported Formats
+//!
+//! | Format          | Example         | Layout                                                       |
+//! |-----------------|-----------------|--------------------------------------------------------------|
+//! | `gm-1968`       | `124379N123456` | Division, series, body, year, plant, serial                  |
+//! | `gm-1972`       | `1Q87H2N500001` | Division, series, body, engine, year, plant, serial          |
+//! | `ford-1966`     | `0F02H123456`   | Year, plant, body, engine, serial                            |
+//! | `chrysler-1966` | `RM23N0A123456` | Car line, price class, body, engine, year, plant, serial     |
+//! | `vw-1969`       | `1102123456`    | Type, year, serial                                           |
+//! | `porsche-1970`  | `9113600123`    | Model, year, engine, body, serial                            |
+//!
+//! These formats encode only the last digit of the model year, so a year digit
+//! may stand for two model years a decade apart. Every plausible year is returned.
+//!
+//! # Examples
+//!
+//! ```
+//! use vin::legacy::{vin_classify, vin_legacy_info};
+//!
+//! assert_eq!(vin_classify("1GKKRNED9EJ262581").unwrap(), "modern");
+//! assert_eq!(vin_classify("124379N123456").unwrap(), "legacy");
+//!
+//! let info = vin_legacy_info("124379N123456").unwrap();
+//! assert_eq!(info.make.as_deref(), Some("Chevrolet"));
+//! assert_eq!(info.model_years, vec![1969]);
+//! ```
+
+use regex::Regex;
+use serde::Serialize;
+use std::sync::LazyLock;
+
+use crate::vin::vin_cleaner_strict;
+
+/// Shortest and longest legacy chassis numbers accepted.
+const LEGACY_LEN: std::ops::RangeInclusive<usize> = 6..=14;
+
+/// A legacy chassis number format.
+struct LegacyFormat {
+    name: &'static str,
+    manufacturer: &'static str,
+    re: Regex,
+    decode: fn(&str) -> Option<LegacyFields>,
+}
+
+/// Fields decoded by the format-specific functions.
+#[derive(Default)]
+struct LegacyFields {
+    make: Option<&'static str>,
+    model: Option<&'static str>,
+    model_years: Vec<i32>,
+    body_code: Option<String>,
+    engine_code: Option<String>,
+    plant_code: Option<String>,
+    serial_number: String,
+}
+
+static LEGACY_FORMATS: LazyLock<Vec<LegacyFormat>> = LazyLock::new(|| {
+    let format = |name, manufacturer, re: &str, decode| LegacyFormat {
+        name,
+        manufacturer,
+        re: Regex::new(re).expect("Invalid legacy VIN regex pattern"),
+        decode,
+    };
+
+    vec![
+        format(
+            "gm-1968",
+            "General Motors",
+            r"^[1-6]\d{4}[8901][A-Z]\d{6}$",
+            gm_1968 as fn(&str) -> Option<LegacyFields>,
+        ),
+        format(
+            "gm-1972",
+            "General Motors",
+            r"^[1-6][A-Z]\d{2}[A-Z][2-9A][A-Z]\d{6}$",
+            gm_1972,
+        ),
+        format(
+            "ford-1966",
+            "Ford Motor Company",
+            r"^\d[A-Z]\d{2}[A-Z]\d{6}$",
+            ford_1966,
+        ),
+        format(
+            "chrysler-1966",
+            "Chrysler Corporation",
+            r"^[A-Z]{2}\d{2}[A-Z0-9]\d[A-Z]\d{6}$",
+            chrysler_1966,
+        ),
+        format("vw-1969", "Volkswagen", r"^[1-4][1-6]\d{8}$", vw_1969),
+        format(
+            "porsche-1970",
+            "Porsche",
+            r"^9(11|12|30)\d{7}$",
+            porsche_1970,
+        ),
+    ]
+});
+
+/// Information decoded from a legacy chassis number.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LegacyInfo {
+    /// The cleaned chassis number.
+    pub vin: String,
+    /// Name of the recognized format (see the module documentation).
+    pub format: Option<&'static str>,
+    /// Manufacturer of the recognized format.
+    pub manufacturer: Option<&'static str>,
+    /// Make, when the format encodes the division or car line.
+    pub make: Option<&'static str>,
+    /// Model line.
+    pub model: Option<&'static str>,
+    /// Every model year matching the encoded year digit, in ascending order.
+    pub model_years: Vec<i32>,
+    /// Body style code.
+    pub body_code: Option<String>,
+    /// Engine code.
+    pub engine_code: Option<String>,
+    /// Assembly plant code.
+    pub plant_code: Option<String>,
+    /// Production serial number, or the whole chassis number for unknown formats.
+    pub serial_number: String,
+}
+
+/// Classifies the input as a modern VIN, a legacy chassis number or invalid.
+///
+/// # Classes
+///
+/// - `modern`: a 17-character VIN accepted by [`crate::vin::vin_is_valid`]
+/// - `legacy`: a chassis number in a supported format (see the module documentation)
+/// - `unknown`: a 6 to 14-character number with at least 4 digits and ending
+///   with a digit, in no supported format
+/// - `invalid`: anything else, including 17-character VINs whose check digit
+///   does not verify
+///
+/// # Arguments
+///
+/// * `vin` - The VIN or chassis number (can be dirty/unformatted, will be cleaned)
+///
+/// # Returns
+///
+/// Always `Some`, with one of `modern`, `legacy`, `unknown` or `invalid`.
+///
+/// # Examples
+///
+/// ```
+/// use vin::legacy::vin_classify;
+///
+/// assert_eq!(vin_classify("WF05XXGCC5FJ00000").unwrap(), "modern");
+/// assert_eq!(vin_classify("Chassis: 0F02H123456").unwrap(), "legacy");
+/// assert_eq!(vin_classify("Invoice 2024001234").unwrap(), "unknown");
+/// assert_eq!(vin_classify("1GKKRNED8EJ262581").unwrap(), "invalid");
+/// assert_eq!(vin_classify("hello").unwrap(), "invalid");
+/// ```
+pub fn vin_classify(vin: &str) -> Option<String> {
+    let class = if vin_cleaner_strict(vin).is_some() {
+        "modern"
+    } else {
+        match legacy_candidate_of(vin) {
+            Some(vin) if legacy_format_of(&vin).is_some() => "legacy",
+            Some(_) => "unknown",
+            None => "invalid",
+        }
+    };
+
+    Some(class.to_string())
+}
+
+/// Decodes a legacy chassis number.
+///
+/// Modern VINs are not decoded here, use [`crate::decode::vin_info`] for them.
+/// A legacy number in an unsupported format is returned with only `vin` and
+/// `serial_number` set.
+///
+/// # Arguments
+///
+/// * `vin` - The chassis number (can be dirty/unformatted, will be cleaned)
+///
+/// # Returns
+///
+/// * `Some(LegacyInfo)` - The decoded record
+/// * `None` - If the input is a modern VIN or not a legacy chassis number
+///
+/// # Examples
+///
+/// ```
+/// use vin::legacy::vin_legacy_info;
+///
+/// let info = vin_legacy_info("0F02H123456").unwrap();
+/// assert_eq!(info.format, Some("ford-1966"));
+/// assert_eq!(info.model_years, vec![1970, 1980]);
+/// assert_eq!(info.plant_code.as_deref(), Some("F"));
+/// assert_eq!(info.serial_number, "123456");
+///
+/// assert!(vin_legacy_info("1GKKRNED9EJ262581").is_none());
+/// ```
+pub fn vin_legacy_info(vin: &str) -> Option<LegacyInfo> {
+    if vin_cleaner_strict(vin).is_some() {
+        return None;
+    }
+    let vin = legacy_candidate_of(vin)?;
+
+    Some(match legacy_format_of(&vin) {
+        Some((format, fields)) => LegacyInfo {
+            format: Some(format.name),
+            manufacturer: Some(format.manufacturer),
+            make: fields.make,
+            model: fields.model,
+            model_years: fields.model_years,
+            body_code: fields.body_code,
+            engine_code: fields.engine_code,
+            plant_code: fields.plant_code,
+            serial_number: fields.serial_number,
+            vin,
+        },
+        None => LegacyInfo {
+            format: None,
+            manufacturer: None,
+            make: None,
+            model: None,
+            model_years: Vec::new(),
+            body_code: None,
+            engine_code: None,
+            plant_code: None,
+            serial_number: vin.clone(),
+            vin,
+        },
+    })
+}
+
+/// Decodes a legacy chassis number into a JSON object.
+///
+/// This is the UDF entry point for `vinDecodeLegacy`. The fields are the ones of
+/// [`LegacyInfo`]; fields that cannot be decoded are `null`.
+///
+/// # Arguments
+///
+/// * `vin` - The chassis number (can be dirty/unformatted, will be cleaned)
+///
+/// # Returns
+///
+/// * `Some(String)` - A JSON object with all decoded fields
+/// * `None` - If the input is a modern VIN or not a legacy chassis number
+///
+/// # Examples
+///
+/// ```
+/// use vin::legacy::vin_decode_legacy;
+///
+/// let json = vin_decode_legacy("9113600123").unwrap();
+/// assert!(json.contains(r#""make":"Porsche","model":"911""#));
+/// assert!(json.contains(r#""model_years":[1973]"#));
+///
+/// assert!(vin_decode_legacy("").is_none());
+/// ```
+pub fn vin_decode_legacy(vin: &str) -> Option<String> {
+    vin_legacy_info(vin).and_then(|info| serde_json::to_string(&info).ok())
+}
+
+/// Returns the legacy chassis number of the input, uppercased.
+///
+/// Words in a supported format are preferred, then the whole input without spaces
+/// and punctuation, so that numbers written in groups (`124379 N 123456`) are still
+/// recognized, and finally any word shaped like a chassis number.
+fn legacy_candidate_of(vin: &str) -> Option<String> {
+    let vin = vin.to_ascii_uppercase();
+    let compact: String = vin.chars().filter(char::is_ascii_alphanumeric).collect();
+    let candidates: Vec<&str> = vin
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .chain([compact.as_str()])
+        .filter(|s| LEGACY_LEN.contains(&s.len()))
+        .collect();
+
+    candidates
+        .iter()
+        .find(|s| legacy_format_of(s).is_some())
+        .or_else(|| candidates.iter().find(|s| is_legacy_of(s)))
+        .map(|s| s.to_string())
+}
+
+/// Decodes an uppercase chassis number with the first supported format it matches.
+fn legacy_format_of(vin: &str) -> Option<(&'static LegacyFormat, LegacyFields)> {
+    LEGACY_FORMATS.iter().find_map(|format| {
+        format
+            .re
+            .is_match(vin)
+            .then(|| (format.decode)(vin))
+            .flatten()
+            .map(|fields| (format, fields))
+    })
+}
+
+/// Returns whether an uppercase alphanumeric string is shaped like a legacy chassis number.
+fn is_legacy_of(s: &str) -> bool {
+    LEGACY_LEN.contains(&s.len())
+        && s.chars().all(|c| c.is_ascii_alphanumeric())
+        && s.chars().filter(char::is_ascii_digit).count() >= 4
+        && s.ends_with(|c: char| c.is_ascii_digit())
+}
+
+/// Model years ending with `digit` between `first` and `last`.
+fn years_of(digit: char, first: i32, last: i32) -> Vec<i32> {
+    let Some(digit) = digit.to_digit(10) else {
+        return Vec::new();
+    };
+    (first..=last).filter(|y| y % 10 == digit as i32).collect()
+}
+
+/// Returns the characters `start..end` of an ASCII chassis number.
+fn section(vin: &str, start: usize, end: usize) -> Option<String> {
+    vin.get(start..end).map(str::to_string)
+}
+
+fn char_at(vin: &str, i: usize) -> char {
+    vin.as_bytes()[i] as char
+}
+
+fn gm_division(c: char) -> Option<&'static str> {
+    match c {
+        '1' => Some("Chevrolet"),
+        '2' => Some("Pontiac"),
+        '3' => Some("Oldsmobile"),
+        '4' => Some("Buick"),
+        '6' => Some("Cadillac"),
+        _ => None,
+    }
+}
+
+/// GM 1968-1971: `124379N123456`.
+fn gm_1968(vin: &str) -> Option<LegacyFields> {
+    Some(LegacyFields {
+        make: gm_division(char_at(vin, 0)),
+        model_years: years_of(char_at(vin, 5), 1968, 1971),
+        body_code: section(vin, 3, 5),
+        plant_code: section(vin, 6, 7),
+        serial_number: section(vin, 7, 13)?,
+        ..Default::default()
+    })
+}
+
+/// GM 1972-1980: `1Q87H2N500001`, with `A` as the 1980 year code.
+fn gm_1972(vin: &str) -> Option<LegacyFields> {
+    let model_years = match char_at(vin, 5) {
+        'A' => vec![1980],
+        c => years_of(c, 1972, 1979),
+    };
+
+    Some(LegacyFields {
+        make: gm_division(char_at(vin, 0)),
+        model_years,
+        body_code: section(vin, 2, 4),
+        engine_code: section(vin, 4, 5),
+        plant_code: section(vin, 6, 7),
+        serial_number: section(vin, 7, 13)?,
+        ..Default::default()
+    })
+}
+
+/// Ford 1966-1980: `0F02H123456`.
+fn ford_1966(vin: &str) -> Option<LegacyFields> {
+    Some(LegacyFields {
+        make: Some("Ford"),
+        model_years: years_of(char_at(vin, 0), 1966, 1980),
+        body_code: section(vin, 2, 4),
+        engine_code: section(vin, 4, 5),
+        plant_code: section(vin, 1, 2),
+        serial_number: section(vin, 5, 11)?,
+        ..Default::default()
+    })
+}
+
+/// Chrysler 1966-1980: `RM23N0A123456`. The first letter is the car line.
+fn chrysler_1966(vin: &str) -> Option<LegacyFields> {
+    let (make, model) = match char_at(vin, 0) {
+        'B' => ("Plymouth", "Barracuda"),
+        'P' => ("Plymouth", "Fury"),
+        'R' => ("Plymouth", "Satellite"),
+        'V' => ("Plymouth", "Valiant"),
+        'D' => ("Dodge", "Polara"),
+        'J' => ("Dodge", "Challenger"),
+        'L' => ("Dodge", "Dart"),
+        'W' => ("Dodge", "Coronet"),
+        'X' => ("Dodge", "Charger"),
+        'C' => ("Chrysler", "Newport"),
+        'Y' => ("Imperial", "Imperial"),
+        _ => return None,
+    };
+
+    Some(LegacyFields {
+        make: Some(make),
+        model: Some(model),
+        model_years: years_of(char_at(vin, 5), 1966, 1980),
+        body_code: section(vin, 2, 4),
+        engine_code: section(vin, 4, 5),
+        plant_code: section(vin, 6, 7),
+        serial_number: section(vin, 7, 13)?,
+    })
+}
+
+/// Volkswagen 1969-1979: `1102123456`. The first two digits are the type.
+fn vw_1969(vin: &str) -> Option<LegacyFields> {
+    let model = match &vin[..2] {
+        "11" => "Beetle",
+        "13" => "Super Beetle",
+        "14" => "Karmann Ghia",
+        "15" => "Beetle Convertible",
+        "21" | "22" | "23" | "24" | "25" | "26" => "Transporter",
+        "31" | "36" => "Type 3",
+        "41" | "42" | "46" => "Type 4",
+        _ => return None,
+    };
+
+    Some(LegacyFields {
+        make: Some("Volkswagen"),
+        model: Some(model),
+        model_years: years_of(char_at(vin, 2), 1969, 1979),
+        body_code: section(vin, 0, 2),
+        serial_number: section(vin, 3, 10)?,
+        ..Default::default()
+    })
+}
+
+/// Porsche 1970-1979: `9113600123`.
+fn porsche_1970(vin: &str) -> Option<LegacyFields> {
+    let model = match &vin[..3] {
+        "911" => "911",
+        "912" => "912",
+        "930" => "911 Turbo",
+        _ => return None,
+    };
+
+    Some(LegacyFields {
+        make: Some("Porsche"),
+        model: Some(model),
+        model_years: years_of(char_at(vin, 3), 1970, 1979),
+        engine_code: section(vin, 4, 5),
+        body_code: section(vin, 5, 6),
+        serial_number: section(vin, 6, 10)?,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vin_classify() {
+        assert_eq!(vin_classify("1GKKRNED9EJ262581").unwrap(), "modern");
+        assert_eq!(vin_classify("VIN: 1gkkrned9ej262581").unwrap(), "modern");
+        assert_eq!(vin_classify("124379N123456").unwrap(), "legacy");
+        assert_eq!(vin_classify("124379 N 123456").unwrap(), "legacy");
+        assert_eq!(vin_classify("1GKKRNED8EJ262581").unwrap(), "invalid");
+        assert_eq!(vin_classify("11101012012345").unwrap(), "unknown");
+        assert_eq!(vin_classify("").unwrap(), "invalid");
+        assert_eq!(vin_classify("file.txt").unwrap(), "invalid");
+        assert_eq!(vin_classify("ABCDEFGH").unwrap(), "invalid");
+    }
+
+    #[test]
+    fn test_vin_classify_not_legacy() {
+        for text in [
+            // Order numbers, the second one shaped like a Volkswagen number
+            "Order #10023456",
+            "PO 4500012345",
+            // Phone numbers
+            "+49 30 1234567",
+            "(555) 010-4477",
+            // Invoice IDs
+            "INV-2024-00123",
+            "Invoice 2024001234",
+            "RE1234567",
+        ] {
+            assert_ne!(vin_classify(text).unwrap(), "legacy", "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_vin_legacy_info_gm() {
+        let info = vin_legacy_info("124379N123456").unwrap();
+        assert_eq!(info.format, Some("gm-1968"));
+        assert_eq!(info.make, Some("Chevrolet"));
+        assert_eq!(info.model_years, vec![1969]);
+        assert_eq!(info.body_code.as_deref(), Some("37"));
+        assert_eq!(info.plant_code.as_deref(), Some("N"));
+        assert_eq!(info.serial_number, "123456");
+
+        let info = vin_legacy_info("1Q87H2N500001").unwrap();
+        assert_eq!(info.format, Some("gm-1972"));
+        assert_eq!(info.model_years, vec![1972]);
+        assert_eq!(info.engine_code.as_deref(), Some("H"));
+
+        let info = vin_legacy_info("2W87WAN100001").unwrap();
+        assert_eq!(info.make, Some("Pontiac"));
+        assert_eq!(info.model_years, vec![1980]);
+    }
+
+    #[test]
+    fn test_vin_legacy_info_ford_chrysler() {
+        let info = vin_legacy_info("9F02M123456").unwrap();
+        assert_eq!(info.format, Some("ford-1966"));
+        assert_eq!(info.model_years, vec![1969, 1979]);
+        assert_eq!(info.body_code.as_deref(), Some("02"));
+        assert_eq!(info.engine_code.as_deref(), Some("M"));
+
+        let info = vin_legacy_info("RM23N0A123456").unwrap();
+        assert_eq!(info.format, Some("chrysler-1966"));
+        assert_eq!(info.make, Some("Plymouth"));
+        assert_eq!(info.model, Some("Satellite"));
+        assert_eq!(info.model_years, vec![1970, 1980]);
+        assert_eq!(info.plant_code.as_deref(), Some("A"));
+    }
+
+    #[test]
+    fn test_vin_legacy_info_vw_porsche() {
+        let info = vin_legacy_info("1102123456").unwrap();
+        assert_eq!(info.format, Some("vw-1969"));
+        assert_eq!(info.model, Some("Beetle"));
+        assert_eq!(info.model_years, vec![1970]);
+        assert_eq!(info.serial_number, "2123456");
+
+        let info = vin_legacy_info("9306800123").unwrap();
+        assert_eq!(info.format, Some("porsche-1970"));
+        assert_eq!(info.model, Some("911 Turbo"));
+        assert_eq!(info.model_years, vec![1976]);
+    }
+
+    #[test]
+    fn test_vin_legacy_info_unknown_format() {
+        let info = vin_legacy_info("11101012012345").unwrap();
+        assert_eq!(info.format, None);
+        assert!(info.model_years.is_empty());
+        assert_eq!(info.serial_number, "11101012012345");
+
+        assert!(vin_legacy_info("WF05XXGCC5FJ00000").is_none());
+        assert!(vin_legacy_info("hello").is_none());
+    }
+}
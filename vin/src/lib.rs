@@ -1,4 +1,25 @@
+pub mod decode;
+pub mod extract;
+pub mod generate;
+pub mod legacy;
+pub mod pseudonym;
+pub mod repair;
+pub mod vds;
 pub mod vin;
+pub mod vpic;
+pub mod wmi_db;
 
 // Re-export public functions for easier doctest and external access
-pub use vin::{get_wmicsv, vin_cleaner, vin_continent, vin_manuf, vin_year, wmi};
+pub use decode::{vin_decode, vin_info, VinInfo};
+pub use extract::vin_extract_all;
+pub use generate::{vin_generate, VinGenerator};
+pub use legacy::{vin_classify, vin_decode_legacy, LegacyInfo};
+pub use pseudonym::vin_pseudonymize;
+pub use repair::vin_repair;
+pub use vds::{vin_vds, vin_vds_at, VdsDecoder, VdsInfo};
+pub use vin::{
+    get_wmicsv, vin_brand, vin_check_digit, vin_check_digit_required, vin_cleaner,
+    vin_cleaner_strict, vin_continent, vin_country, vin_group, vin_is_valid, vin_manuf, vin_year,
+    vin_year_at, vin_year_candidates, wmi,
+};
+pub use vpic::{vin_decode_vpic, VpicIndex};
@@ -0,0 +1,291 @@
+//! Keyed pseudonymization of VINs.
+//!
+//! Sharing vehicle data often requires hiding the VIN while keeping what
+//! analytics need from it. [`vin_pseudonymize`] keeps the WMI, the vehicle
+//! descriptor, the model year and the plant, and replaces the serial number
+//! with characters derived from an HMAC-SHA256 of the VIN. The result is a
+//! well-formed VIN, so [`crate::vin::vin_manuf`], [`crate::vin::vin_year`] and
+//! the other UDFs return the same values as for the original.
+//!
+//! The same VIN and key always give the same pseudonym, so joins and distinct
+//! counts still work on pseudonymized data. Without the key, the original serial
+//! cannot be recovered or confirmed.
+//!
+//! # Key Configuration
+//!
+//! The key is read, in order, from:
+//!
+//! 1. The file passed as argument to `vin-pseudonymize`
+//! 2. The file named by the `VIN_PSEUDONYM_KEY_FILE` environment variable
+//! 3. The `VIN_PSEUDONYM_KEY` environment variable
+//!
+//! ```xml
+//! <command>vin-pseudonymize /run/secrets/vin-pseudonym-key</command>
+//! ```
+
+use anyhow::{bail, Context, Result};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::vin::{
+    check_digit_of, check_digit_required_of, check_digit_valid_of, is_valid_of, vin_cleaner,
+};
+
+/// Environment variable with the path of the file holding the key.
+pub const PSEUDONYM_KEY_FILE_ENV: &str = "VIN_PSEUDONYM_KEY_FILE";
+
+/// Environment variable holding the key.
+pub const PSEUDONYM_KEY_ENV: &str = "VIN_PSEUDONYM_KEY";
+
+/// Letters allowed in a VIN (everything but I, O and Q).
+const VIN_LETTERS: &[u8] = b"ABCDEFGHJKLMNPRSTUVWXYZ";
+
+static PSEUDONYM_KEY: OnceLock<Option<Vec<u8>>> = OnceLock::new();
+
+/// Loads the global pseudonymization key.
+///
+/// # Arguments
+///
+/// * `path` - File holding the key. When `None`, `VIN_PSEUDONYM_KEY_FILE` and
+///   then `VIN_PSEUDONYM_KEY` are used.
+///
+/// # Errors
+///
+/// Fails if no key is configured, the key file cannot be read, the key is empty,
+/// or the key was already initialized.
+pub fn init_pseudonym_key<P: AsRef<Path>>(path: Option<P>) -> Result<()> {
+    let key = match path {
+        Some(path) => read_key_file(path.as_ref())?,
+        None => load_from_env()?,
+    };
+
+    if PSEUDONYM_KEY.set(Some(key)).is_err() {
+        bail!("Pseudonymization key is already initialized");
+    }
+
+    Ok(())
+}
+
+/// Returns the global pseudonymization key, loading it from the environment on first use.
+fn pseudonym_key() -> Option<&'static [u8]> {
+    PSEUDONYM_KEY
+        .get_or_init(|| {
+            load_from_env().map(Some).unwrap_or_else(|e| {
+                eprintln!("ERROR: {:#}", e);
+                None
+            })
+        })
+        .as_deref()
+}
+
+fn load_from_env() -> Result<Vec<u8>> {
+    if let Some(path) = env::var_os(PSEUDONYM_KEY_FILE_ENV).filter(|path| !path.is_empty()) {
+        return read_key_file(Path::new(&path));
+    }
+
+    if let Ok(key) = env::var(PSEUDONYM_KEY_ENV) {
+        let key = key.trim();
+        if !key.is_empty() {
+            return Ok(key.as_bytes().to_vec());
+        }
+    }
+
+    bail!(
+        "No pseudonymization key found. Set one of:\n\
+         - vin-pseudonymize /path/to/key.txt\n\
+         - {}=/path/to/key.txt\n\
+         - {}=...",
+        PSEUDONYM_KEY_FILE_ENV,
+        PSEUDONYM_KEY_ENV
+    )
+}
+
+fn read_key_file(path: &Path) -> Result<Vec<u8>> {
+    let key = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read pseudonymization key from {:?}", path))?;
+    let key = key.trim();
+    if key.is_empty() {
+        bail!("Pseudonymization key file {:?} is empty", path);
+    }
+    Ok(key.as_bytes().to_vec())
+}
+
+/// Pseudonymizes a VIN with the global key.
+///
+/// See [`vin_pseudonymize_with_key`] for the characters that are kept. The key is
+/// set with [`init_pseudonym_key`], or read from the environment on first use.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (can be dirty/unformatted, will be cleaned)
+///
+/// # Returns
+///
+/// * `Some(String)` - The pseudonymized VIN
+/// * `None` - If the VIN is invalid or no key is configured
+pub fn vin_pseudonymize(vin: &str) -> Option<String> {
+    vin_pseudonymize_with_key(vin, pseudonym_key()?)
+}
+
+/// Pseudonymizes a VIN with the given key.
+///
+/// # Kept Characters
+///
+/// - Positions 1-8: the WMI and the vehicle descriptor (model, body, engine)
+/// - Position 10: the model year
+/// - Position 11: the plant
+/// - Positions 12-14 when the WMI has 6 characters (third character `9`), as
+///   they complete the manufacturer code
+///
+/// The other serial characters are replaced, digits with digits and letters
+/// with letters, so a numeric serial stays numeric. The check digit (position 9)
+/// is recomputed where it is mandatory or was valid in the input, and kept
+/// otherwise.
+///
+/// Distinct VINs may share a pseudonym, as the serial only has a million
+/// possible values when numeric. Count distinct pseudonyms per model and year
+/// rather than relying on them being unique across a very large fleet.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (can be dirty/unformatted, will be cleaned)
+/// * `key` - The secret key of the HMAC
+///
+/// # Returns
+///
+/// * `Some(String)` - The pseudonymized VIN
+/// * `None` - If the input does not contain a valid 17-character VIN
+///
+/// # Examples
+///
+/// ```
+/// use vin::pseudonym::vin_pseudonymize_with_key;
+/// use vin::{vin_is_valid, vin_manuf, vin_year_at};
+///
+/// let pseudonym = vin_pseudonymize_with_key("1GKKRNED9EJ262581", b"secret").unwrap();
+/// assert_ne!(pseudonym, "1GKKRNED9EJ262581");
+/// assert_eq!(&pseudonym[..8], "1GKKRNED");
+/// assert_eq!(&pseudonym[9..11], "EJ");
+///
+/// // Still a valid VIN decoding to the same manufacturer and year
+/// assert_eq!(vin_is_valid(&pseudonym).unwrap(), "true");
+/// assert_eq!(vin_manuf(&pseudonym), vin_manuf("1GKKRNED9EJ262581"));
+/// assert_eq!(vin_year_at(&pseudonym, 2024).unwrap(), "2014");
+///
+/// // Deterministic for a given key
+/// assert_eq!(
+///     vin_pseudonymize_with_key("1gkkrned9ej262581", b"secret").unwrap(),
+///     pseudonym
+/// );
+///
+/// assert!(vin_pseudonymize_with_key("invalid", b"secret").is_none());
+/// ```
+pub fn vin_pseudonymize_with_key(vin: &str, key: &[u8]) -> Option<String> {
+    let vin = vin_cleaner(vin)?;
+    if !is_valid_of(&vin) {
+        return None;
+    }
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).ok()?;
+    mac.update(vin.as_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let serial_start = if vin.as_bytes()[2] == b'9' { 14 } else { 11 };
+    let mut chars = vin.as_bytes().to_vec();
+
+    for (i, bytes) in (serial_start..17).zip(digest.chunks_exact(2)) {
+        let n = u16::from_be_bytes([bytes[0], bytes[1]]) as usize;
+        chars[i] = if chars[i].is_ascii_digit() {
+            b'0' + (n % 10) as u8
+        } else {
+            VIN_LETTERS[n % VIN_LETTERS.len()]
+        };
+    }
+
+    let mut pseudonym = String::from_utf8(chars).ok()?;
+    if check_digit_required_of(&vin) || check_digit_valid_of(&vin) {
+        let check_digit = check_digit_of(&pseudonym)?;
+        pseudonym.replace_range(8..9, check_digit.encode_utf8(&mut [0; 4]));
+    }
+
+    Some(pseudonym)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vin::{vin_country, vin_manuf, vin_year_at, wmi};
+
+    const KEY: &[u8] = b"test-key";
+
+    #[test]
+    fn test_vin_pseudonymize_keeps_fields() {
+        for vin in [
+            "1GKKRNED9EJ262581",
+            "2A4GP54L16R805929",
+            "JM1BL1M72C1587426",
+            "WF05XXGCC5FJ00000",
+        ] {
+            let pseudonym = vin_pseudonymize_with_key(vin, KEY).unwrap();
+            assert_ne!(pseudonym, vin);
+            assert_eq!(pseudonym[..8], vin[..8]);
+            assert_eq!(pseudonym[9..11], vin[9..11]);
+            assert!(is_valid_of(&pseudonym), "{}", pseudonym);
+            assert_eq!(wmi(&pseudonym), wmi(vin));
+            assert_eq!(vin_manuf(&pseudonym), vin_manuf(vin));
+            assert_eq!(vin_country(&pseudonym), vin_country(vin));
+            assert_eq!(vin_year_at(&pseudonym, 2024), vin_year_at(vin, 2024));
+        }
+    }
+
+    #[test]
+    fn test_vin_pseudonymize_check_digit() {
+        // Mandatory check digit is recomputed
+        let pseudonym = vin_pseudonymize_with_key("1GKKRNED9EJ262581", KEY).unwrap();
+        assert!(check_digit_valid_of(&pseudonym));
+
+        // Position 9 is not a check digit here, so it is kept
+        let pseudonym = vin_pseudonymize_with_key("WF05XXGCC5FJ00000", KEY).unwrap();
+        assert_eq!(&pseudonym[8..9], "C");
+    }
+
+    #[test]
+    fn test_vin_pseudonymize_character_classes() {
+        let pseudonym = vin_pseudonymize_with_key("1GKKRNED9EJ262581", KEY).unwrap();
+        assert!(pseudonym[11..].chars().all(|c| c.is_ascii_digit()));
+
+        let pseudonym = vin_pseudonymize_with_key("VF1AB000000AB1234", KEY).unwrap();
+        assert!(pseudonym[11..13].chars().all(|c| c.is_ascii_uppercase()));
+        assert!(!pseudonym[11..13].contains(['I', 'O', 'Q']));
+        assert!(pseudonym[13..].chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_vin_pseudonymize_six_character_wmi() {
+        let pseudonym = vin_pseudonymize_with_key("WA9ZZZ12345AB1234", KEY).unwrap();
+        assert_eq!(pseudonym[..14], *"WA9ZZZ12345AB1");
+        assert_eq!(wmi(&pseudonym), wmi("WA9ZZZ12345AB1234"));
+    }
+
+    #[test]
+    fn test_vin_pseudonymize_key() {
+        let a = vin_pseudonymize_with_key("1GKKRNED9EJ262581", b"key-a").unwrap();
+        let b = vin_pseudonymize_with_key("1GKKRNED9EJ262581", b"key-b").unwrap();
+        assert_ne!(a, b);
+        assert_eq!(
+            vin_pseudonymize_with_key("VIN: 1GKKRNED9EJ262581", b"key-a").unwrap(),
+            a
+        );
+    }
+
+    #[test]
+    fn test_vin_pseudonymize_invalid() {
+        assert!(vin_pseudonymize_with_key("", KEY).is_none());
+        assert!(vin_pseudonymize_with_key("1GKKRNED8EJ262581", KEY).is_none());
+        assert!(vin_pseudonymize_with_key("1GKKRNED9EJ26258", KEY).is_none());
+    }
+}
@@ -0,0 +1,295 @@
+//! Correction of mistyped and OCR'd VINs using the check digit.
+//!
+//! Hand-entered or scanned VINs often contain `I`, `O` or `Q` in place of `1` and
+//! `0`, or one wrong, missing or extra character. [`vin_repair`] tries the known
+//! substitutions first, then single-character edits, and keeps only the
+//! candidates whose check digit (position 9) verifies.
+//!
+//! # Status
+//!
+//! | Status      | Meaning                                                           |
+//! |-------------|-------------------------------------------------------------------|
+//! | `unchanged` | The VIN was already valid                                         |
+//! | `corrected` | Exactly one correction produces a valid VIN                       |
+//! | `ambiguous` | Several corrections are valid, the VIN is returned as found       |
+//! | `invalid`   | No correction produces a valid VIN, the VIN is returned as found  |
+//!
+//! # Examples
+//!
+//! ```
+//! use vin::repair::vin_repair;
+//!
+//! assert_eq!(
+//!     vin_repair("1GKKRNED9EJ262S81"),
+//!     Some(("1GKKRNED9EJ262581".to_string(), "corrected"))
+//! );
+//! ```
+
+use std::collections::BTreeSet;
+
+use crate::vin::{check_digit_required_of, is_valid_of};
+
+/// Characters allowed in a VIN (everything but I, O and Q).
+const VIN_CHARS: &str = "ABCDEFGHJKLMNPRSTUVWXYZ0123456789";
+
+/// Pairs of characters that are commonly confused when reading or typing a VIN.
+const CONFUSABLES: [(char, char); 13] = [
+    ('0', 'D'),
+    ('1', '7'),
+    ('2', 'Z'),
+    ('3', '8'),
+    ('4', 'A'),
+    ('5', 'S'),
+    ('6', 'G'),
+    ('7', 'T'),
+    ('8', 'B'),
+    ('C', 'G'),
+    ('E', 'F'),
+    ('M', 'N'),
+    ('U', 'V'),
+];
+
+/// Repairs a VIN and reports whether it was changed.
+///
+/// # Correction Steps
+///
+/// 1. `I` is replaced with `1`, `O` and `Q` with `0`; these letters never
+///    appear in a VIN
+/// 2. If the VIN does not verify, every single substitution of a commonly
+///    confused character (`5`/`S`, `8`/`B`, `0`/`D`, ...) is tried
+/// 3. If none verifies, every single-character substitution is tried for
+///    17-character input, every insertion for 16 characters and every
+///    deletion for 18 characters
+///
+/// The first step producing candidates decides the result. Candidates from
+/// steps 2 and 3 must also have a numeric serial (positions 14-17) and a valid
+/// model year character in position 10, as required for North American VINs.
+///
+/// Steps 2 and 3 rely on the check digit, so they only apply where it is
+/// mandatory (see [`crate::vin::vin_check_digit_required`]). Other VINs are only
+/// transliterated.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (can be dirty/unformatted, will be cleaned)
+///
+/// # Returns
+///
+/// * `Some((vin, status))` - The repaired VIN (or the VIN as found if it could not be
+///   repaired) and one of `unchanged`, `corrected`, `ambiguous` or `invalid`
+/// * `None` - If the input does not contain a 16 to 18-character VIN candidate
+///
+/// # Examples
+///
+/// ```
+/// use vin::repair::vin_repair;
+///
+/// // Already valid
+/// assert_eq!(
+///     vin_repair("1gkkrned9ej262581"),
+///     Some(("1GKKRNED9EJ262581".to_string(), "unchanged"))
+/// );
+///
+/// // I typed instead of 1
+/// assert_eq!(
+///     vin_repair("JTDKN3DU9F042I684"),
+///     Some(("JTDKN3DU9F0421684".to_string(), "corrected"))
+/// );
+///
+/// // Missing character
+/// assert_eq!(
+///     vin_repair("VIN: 1GKKRNED9EJ26258").unwrap().1,
+///     "ambiguous"
+/// );
+///
+/// assert!(vin_repair("invalid").is_none());
+/// ```
+pub fn vin_repair(vin: &str) -> Option<(String, &'static str)> {
+    let found = candidate_of(vin)?;
+    let vin: String = found
+        .chars()
+        .map(|c| match c {
+            'I' => '1',
+            'O' | 'Q' => '0',
+            c => c,
+        })
+        .collect();
+
+    if is_valid_of(&vin) {
+        let status = if vin == found {
+            "unchanged"
+        } else {
+            "corrected"
+        };
+        return Some((vin, status));
+    }
+
+    if !check_digit_required_of(&vin) {
+        return Some((found, "invalid"));
+    }
+
+    let mut candidates = substitutions(&vin, |c| confusables_of(c).collect());
+    if candidates.is_empty() {
+        candidates = match vin.len() {
+            16 => insertions(&vin),
+            18 => deletions(&vin),
+            _ => substitutions(&vin, |_| VIN_CHARS.chars().collect()),
+        };
+    }
+
+    let mut candidates = candidates.into_iter();
+    Some(match (candidates.next(), candidates.next()) {
+        (Some(repaired), None) => (repaired, "corrected"),
+        (Some(_), Some(_)) => (found, "ambiguous"),
+        (None, _) => (found, "invalid"),
+    })
+}
+
+/// Returns the first 16 to 18-character alphanumeric word of the input, uppercased.
+///
+/// Falls back to the whole input without spaces and punctuation, so that VINs
+/// written in groups (`1GK-KRNED9-EJ262581`) are still found.
+fn candidate_of(vin: &str) -> Option<String> {
+    let vin = vin.to_ascii_uppercase();
+    let is_candidate = |s: &str| (16..=18).contains(&s.len());
+
+    if let Some(word) = vin
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .find(|word| is_candidate(word))
+    {
+        return Some(word.to_string());
+    }
+
+    let compact: String = vin.chars().filter(char::is_ascii_alphanumeric).collect();
+    is_candidate(&compact).then_some(compact)
+}
+
+/// Returns the characters commonly confused with `c`.
+fn confusables_of(c: char) -> impl Iterator<Item = char> {
+    CONFUSABLES.iter().filter_map(move |&(a, b)| match c {
+        _ if c == a => Some(b),
+        _ if c == b => Some(a),
+        _ => None,
+    })
+}
+
+/// Valid VINs obtained by replacing one character with one of `replacements(c)`.
+fn substitutions(vin: &str, replacements: impl Fn(char) -> Vec<char>) -> BTreeSet<String> {
+    let chars: Vec<char> = vin.chars().collect();
+    let mut valid = BTreeSet::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        for replacement in replacements(c) {
+            if replacement == c {
+                continue;
+            }
+            let mut candidate = chars.clone();
+            candidate[i] = replacement;
+            let candidate: String = candidate.into_iter().collect();
+            if is_repaired(&candidate) {
+                valid.insert(candidate);
+            }
+        }
+    }
+
+    valid
+}
+
+/// Valid VINs obtained by inserting one character into a 16-character VIN.
+fn insertions(vin: &str) -> BTreeSet<String> {
+    (0..=vin.len())
+        .flat_map(|i| {
+            VIN_CHARS
+                .chars()
+                .map(move |c| format!("{}{}{}", &vin[..i], c, &vin[i..]))
+        })
+        .filter(|candidate| is_repaired(candidate))
+        .collect()
+}
+
+/// Valid VINs obtained by deleting one character of an 18-character VIN.
+fn deletions(vin: &str) -> BTreeSet<String> {
+    (0..vin.len())
+        .map(|i| format!("{}{}", &vin[..i], &vin[i + 1..]))
+        .filter(|candidate| is_repaired(candidate))
+        .collect()
+}
+
+/// Returns `true` if a candidate is valid, with a valid model year character.
+fn is_repaired(vin: &str) -> bool {
+    is_valid_of(vin) && !matches!(vin.chars().nth(9), Some('0' | 'U' | 'Z'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vin_repair_unchanged() {
+        assert_eq!(
+            vin_repair("1GKKRNED9EJ262581"),
+            Some(("1GKKRNED9EJ262581".to_string(), "unchanged"))
+        );
+        assert_eq!(
+            vin_repair("WF05XXGCC5FJ00000"),
+            Some(("WF05XXGCC5FJ00000".to_string(), "unchanged"))
+        );
+    }
+
+    #[test]
+    fn test_vin_repair_transliteration() {
+        assert_eq!(
+            vin_repair("JTDKN3DU9FO421684"),
+            Some(("JTDKN3DU9F0421684".to_string(), "corrected"))
+        );
+        assert_eq!(
+            vin_repair("WFO5XXGCC5FJOOOOO"),
+            Some(("WF05XXGCC5FJ00000".to_string(), "corrected"))
+        );
+    }
+
+    #[test]
+    fn test_vin_repair_confusables() {
+        assert_eq!(
+            vin_repair("1GKKRNED9EJ262S81"),
+            Some(("1GKKRNED9EJ262581".to_string(), "corrected"))
+        );
+        assert_eq!(
+            vin_repair("1GKKRNED9EJ26258I"),
+            Some(("1GKKRNED9EJ262581".to_string(), "corrected"))
+        );
+    }
+
+    #[test]
+    fn test_vin_repair_deletion() {
+        assert_eq!(
+            vin_repair("1GKKRNED9EJ2625A81"),
+            Some(("1GKKRNED9EJ262581".to_string(), "corrected"))
+        );
+    }
+
+    #[test]
+    fn test_vin_repair_not_repairable() {
+        // No check digit to rely on outside North America and China
+        assert_eq!(
+            vin_repair("WF05XXGCC5FJ0000"),
+            Some(("WF05XXGCC5FJ0000".to_string(), "invalid"))
+        );
+        assert!(vin_repair("").is_none());
+        assert!(vin_repair("1GKKRNED9EJ").is_none());
+    }
+
+    #[test]
+    fn test_vin_repair_always_verifies() {
+        for vin in [
+            "1GKKRNED8EJ262581",
+            "2A4GP54L16R8O5929",
+            "1G1ND52F14M712344",
+        ] {
+            let (repaired, status) = vin_repair(vin).unwrap();
+            if status == "corrected" {
+                assert!(is_valid_of(&repaired), "{}", vin);
+            }
+        }
+    }
+}
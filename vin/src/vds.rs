@@ -0,0 +1,499 @@
+//! Manufacturer-specific decoding of the Vehicle Descriptor Section (VDS).
+//!
+//! Positions 4-8 of a VIN describe the vehicle (model line, body, engine,
+//! restraint system), but every manufacturer uses its own encoding. A
+//! [`VdsDecoder`] decodes them for one manufacturer, and the [`VdsRegistry`]
+//! selects the decoder from the WMI.
+//!
+//! # Built-in Decoders
+//!
+//! The built-in decoders are [`TableDecoder`]s driven by the CSV tables embedded
+//! from `src/vds/`:
+//!
+//! | Table        | WMI prefixes                                     |
+//! |--------------|--------------------------------------------------|
+//! | `gm.csv`     | `1G`, `2G`, `3G`                                 |
+//! | `ford.csv`   | `1F`, `2F`, `3F`, `1ZV`                          |
+//! | `toyota.csv` | `JT`, `2T`, `4T`, `5T`, `5YF`                    |
+//! | `vw.csv`     | `WVW`, `WVG`, `WV1`, `WV2`, `1VW`, `3VW`, `9BW`  |
+//!
+//! # Adding a Manufacturer
+//!
+//! Add a table to `src/vds/` and register it in [`VdsRegistry::builtin`]. The
+//! table has a header row and one rule per line:
+//!
+//! ```text
+//! Prefix, Field, Positions, Code, Value, Years
+//! 1FT, model, 6, 1, F-150, 2004-2020
+//! , engine, 8, F, 5.0L V8, 2011-2020
+//! ```
+//!
+//! - `Prefix`: the rule only applies to VINs starting with it (empty for all)
+//! - `Field`: `model`, `body_style` or `engine`
+//! - `Positions`: 1-based VIN position (`8`) or inclusive range (`6-7`)
+//! - `Code`: the characters expected at these positions
+//! - `Years`: model years the rule applies to (`2011-2020`, `2015-`), empty for all
+//!
+//! The first matching rule of each field wins. Decoders that cannot be
+//! expressed as a table can implement [`VdsDecoder`] directly.
+//!
+//! # Examples
+//!
+//! ```
+//! use vin::vds::vin_vds;
+//!
+//! let vds = vin_vds("1FTEW1CM9BFA74557").unwrap();
+//! assert_eq!(vds.model.as_deref(), Some("F-150"));
+//! assert_eq!(vds.body_style.as_deref(), Some("SuperCrew"));
+//! assert_eq!(vds.engine.as_deref(), Some("3.7L V6"));
+//! ```
+
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Local};
+use csv::ReaderBuilder;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, LazyLock};
+
+use crate::vin::{vin_cleaner, wmi_of, year_at_of};
+
+static GM_DATA: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "src/vds/gm.csv"));
+static FORD_DATA: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "src/vds/ford.csv"));
+static TOYOTA_DATA: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/",
+    "src/vds/toyota.csv"
+));
+static VW_DATA: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "src/vds/vw.csv"));
+
+static VDS_REGISTRY: LazyLock<VdsRegistry> = LazyLock::new(VdsRegistry::builtin);
+
+/// Vehicle attributes decoded from the VDS.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct VdsInfo {
+    /// Model line, e.g. "F-150".
+    pub model: Option<String>,
+    /// Body style, e.g. "4-Door Sedan".
+    pub body_style: Option<String>,
+    /// Engine, e.g. "5.0L V8".
+    pub engine: Option<String>,
+}
+
+/// Decodes the VDS of the VINs of one manufacturer.
+pub trait VdsDecoder: Send + Sync {
+    /// Decodes an already cleaned 17-character VIN.
+    ///
+    /// `model_year` is the decoded model year, since manufacturers reuse codes
+    /// across years.
+    fn decode(&self, vin: &str, model_year: Option<i32>) -> VdsInfo;
+}
+
+/// The fields a [`TableDecoder`] rule can set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Model,
+    BodyStyle,
+    Engine,
+}
+
+/// One line of a VDS table.
+#[derive(Debug, Clone)]
+struct Rule {
+    prefix: String,
+    field: Field,
+    positions: RangeInclusive<usize>,
+    code: String,
+    value: String,
+    years: Option<RangeInclusive<i32>>,
+}
+
+impl Rule {
+    fn matches(&self, vin: &str, model_year: Option<i32>) -> bool {
+        let (start, end) = (*self.positions.start(), *self.positions.end());
+
+        vin.starts_with(&self.prefix)
+            && vin.get(start - 1..end) == Some(self.code.as_str())
+            && match (&self.years, model_year) {
+                (None, _) => true,
+                (Some(years), Some(year)) => years.contains(&year),
+                (Some(_), None) => false,
+            }
+    }
+}
+
+/// A [`VdsDecoder`] driven by a CSV table of rules.
+///
+/// See the [module documentation](self) for the table format.
+#[derive(Debug, Clone, Default)]
+pub struct TableDecoder {
+    rules: Vec<Rule>,
+}
+
+impl TableDecoder {
+    /// Parses a VDS table.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the line of the first invalid rule.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vin::vds::{TableDecoder, VdsDecoder};
+    ///
+    /// let decoder = TableDecoder::from_csv("Prefix, Field, Positions, Code, Value, Years\n\
+    ///                                       , model, 4-5, AB, Example, 2010-\n").unwrap();
+    /// let vds = decoder.decode("1XYAB000000000000", Some(2012));
+    /// assert_eq!(vds.model.as_deref(), Some("Example"));
+    ///
+    /// assert!(TableDecoder::from_csv("Prefix, Field, Positions, Code, Value, Years\n\
+    ///                                 , color, 4, A, Red,\n").is_err());
+    /// ```
+    pub fn from_csv(data: &str) -> Result<Self> {
+        let mut rules = Vec::new();
+        let mut reader = ReaderBuilder::new()
+            .delimiter(b',')
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(data.as_bytes());
+
+        for (i, record) in reader.records().enumerate() {
+            let line = i + 2;
+            let record = record.with_context(|| format!("Invalid VDS table line {}", line))?;
+            if record.len() < 5 {
+                bail!(
+                    "Invalid VDS table line {}: expected at least 5 columns",
+                    line
+                );
+            }
+
+            let field = match &record[1] {
+                "model" => Field::Model,
+                "body_style" => Field::BodyStyle,
+                "engine" => Field::Engine,
+                other => bail!("Invalid VDS table line {}: unknown field {:?}", line, other),
+            };
+            let positions = parse_range(&record[2])
+                .filter(|p| *p.start() >= 1 && *p.end() <= 17)
+                .with_context(|| format!("Invalid VDS table line {}: bad positions", line))?;
+            let code = record[3].to_string();
+            if code.len() != positions.clone().count() {
+                bail!(
+                    "Invalid VDS table line {}: code does not fit positions",
+                    line
+                );
+            }
+            let years = match record.get(5).unwrap_or_default() {
+                "" => None,
+                years => Some(
+                    parse_range(years)
+                        .with_context(|| format!("Invalid VDS table line {}: bad years", line))?,
+                ),
+            };
+
+            rules.push(Rule {
+                prefix: record[0].to_string(),
+                field,
+                positions,
+                code,
+                value: record[4].to_string(),
+                years,
+            });
+        }
+
+        Ok(TableDecoder { rules })
+    }
+}
+
+impl VdsDecoder for TableDecoder {
+    fn decode(&self, vin: &str, model_year: Option<i32>) -> VdsInfo {
+        let value = |field: Field| {
+            self.rules
+                .iter()
+                .find(|rule| rule.field == field && rule.matches(vin, model_year))
+                .map(|rule| rule.value.clone())
+        };
+
+        VdsInfo {
+            model: value(Field::Model),
+            body_style: value(Field::BodyStyle),
+            engine: value(Field::Engine),
+        }
+    }
+}
+
+/// Parses `N`, `N-M` or `N-` (unbounded end) into an inclusive range.
+fn parse_range<T>(s: &str) -> Option<RangeInclusive<T>>
+where
+    T: std::str::FromStr + Copy + Bounded,
+{
+    match s.split_once('-') {
+        Some((start, "")) => Some(start.trim().parse().ok()?..=T::MAX),
+        Some((start, end)) => Some(start.trim().parse().ok()?..=end.trim().parse().ok()?),
+        None => {
+            let n = s.parse().ok()?;
+            Some(n..=n)
+        }
+    }
+}
+
+/// Integer types with an upper bound, for open-ended ranges.
+trait Bounded {
+    const MAX: Self;
+}
+
+impl Bounded for usize {
+    const MAX: Self = usize::MAX;
+}
+
+impl Bounded for i32 {
+    const MAX: Self = i32::MAX;
+}
+
+/// VDS decoders keyed by WMI prefix.
+#[derive(Clone, Default)]
+pub struct VdsRegistry {
+    decoders: HashMap<String, Arc<dyn VdsDecoder>>,
+}
+
+impl VdsRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry with the built-in decoders for GM, Ford, Toyota and Volkswagen.
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+        let tables: [(&str, &[&str]); 4] = [
+            (GM_DATA, &["1G", "2G", "3G"]),
+            (FORD_DATA, &["1F", "2F", "3F", "1ZV"]),
+            (TOYOTA_DATA, &["JT", "2T", "4T", "5T", "5YF"]),
+            (VW_DATA, &["WVW", "WVG", "WV1", "WV2", "1VW", "3VW", "9BW"]),
+        ];
+
+        for (data, prefixes) in tables {
+            let decoder = TableDecoder::from_csv(data).expect("Invalid embedded VDS table");
+            registry.register(prefixes, Arc::new(decoder));
+        }
+
+        registry
+    }
+
+    /// Registers a decoder for VINs whose WMI starts with one of `prefixes`.
+    ///
+    /// Prefixes are 2 or 3 characters long; a 3-character prefix takes precedence
+    /// over a 2-character one, and registering a prefix again replaces its decoder.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use vin::vds::{TableDecoder, VdsRegistry};
+    ///
+    /// let table = "Prefix, Field, Positions, Code, Value, Years\n, model, 4, A, Example,\n";
+    /// let mut registry = VdsRegistry::new();
+    /// registry.register(&["ZZ"], Arc::new(TableDecoder::from_csv(table).unwrap()));
+    ///
+    /// let vds = registry.decode("ZZZA0000000000000", None).unwrap();
+    /// assert_eq!(vds.model.as_deref(), Some("Example"));
+    /// assert!(registry.decode("1GKKRNED9EJ262581", Some(2014)).is_none());
+    /// ```
+    pub fn register(&mut self, prefixes: &[&str], decoder: Arc<dyn VdsDecoder>) {
+        for prefix in prefixes {
+            self.decoders.insert(prefix.to_string(), decoder.clone());
+        }
+    }
+
+    /// Returns the decoder registered for the WMI of an already cleaned VIN.
+    pub fn decoder_for(&self, vin: &str) -> Option<&dyn VdsDecoder> {
+        [3, 2]
+            .iter()
+            .filter_map(|len| vin.get(..*len))
+            .find_map(|prefix| self.decoders.get(prefix))
+            .map(|decoder| decoder.as_ref())
+    }
+
+    /// Decodes the VDS of an already cleaned VIN.
+    ///
+    /// `model_year` selects the rules that apply, as in [`VdsDecoder::decode`].
+    /// Returns `None` if the VIN is not 17 characters long or no decoder is
+    /// registered for its WMI.
+    pub fn decode(&self, vin: &str, model_year: Option<i32>) -> Option<VdsInfo> {
+        if vin.len() != 17 {
+            return None;
+        }
+
+        self.decoder_for(vin)
+            .map(|decoder| decoder.decode(vin, model_year))
+    }
+}
+
+/// Returns the registry with the built-in decoders.
+pub fn vds_registry() -> &'static VdsRegistry {
+    &VDS_REGISTRY
+}
+
+/// Decodes the model, body style and engine of a VIN.
+///
+/// The rules are selected with the model year of [`crate::vin::vin_year`],
+/// relative to the current year. Use [`vin_vds_at`] for a fixed reference year.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (can be dirty/unformatted, will be cleaned)
+///
+/// # Returns
+///
+/// * `Some(VdsInfo)` - The decoded fields; each is `None` when no rule of the
+///   manufacturer's table matches
+/// * `None` - If no VIN was found or there is no decoder for its manufacturer
+///
+/// # Examples
+///
+/// ```
+/// use vin::vds::vin_vds;
+///
+/// let vds = vin_vds("JTDKN3DU9F0421684").unwrap();
+/// assert_eq!(vds.model.as_deref(), Some("Prius"));
+/// assert_eq!(vds.body_style.as_deref(), Some("Hatchback"));
+///
+/// // No decoder for Kia
+/// assert!(vin_vds("KNMCSHLMS6P600875").is_none());
+/// ```
+pub fn vin_vds(vin: &str) -> Option<VdsInfo> {
+    vin_vds_at(vin, Local::now().year())
+}
+
+/// Same as [`vin_vds`], decoding the model year relative to a reference year
+/// as [`crate::vin::vin_year_at`] does.
+///
+/// # Examples
+///
+/// ```
+/// use vin::vds::vin_vds_at;
+///
+/// let vds = vin_vds_at("WVWZZZ1JZ3W000000", 2024).unwrap();
+/// assert_eq!(vds.model.as_deref(), Some("Golf IV"));
+/// ```
+pub fn vin_vds_at(vin: &str, reference_year: i32) -> Option<VdsInfo> {
+    let vin = vin_cleaner(vin)?;
+
+    // Small manufacturers (3rd character `9`) share the WMI of their region
+    if wmi_of(&vin).is_some_and(|wmi| wmi.len() == 6) {
+        return None;
+    }
+
+    vds_registry().decode(&vin, year_at_of(&vin, reference_year))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_tables() {
+        for data in [GM_DATA, FORD_DATA, TOYOTA_DATA, VW_DATA] {
+            assert!(TableDecoder::from_csv(data).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_vin_vds() {
+        let cases = [
+            (
+                "1G1JC1249Y7150000",
+                Some("Cavalier"),
+                Some("2-Door Coupe"),
+                Some("2.2L I4 (LN2)"),
+            ),
+            (
+                "1G1ND52F14M712344",
+                Some("Classic"),
+                Some("4-Door Sedan"),
+                Some("2.2L I4 Ecotec (L61)"),
+            ),
+            (
+                "1FAFP34P63W132895",
+                Some("Focus"),
+                None,
+                Some("2.0L I4 Zetec"),
+            ),
+            ("1GKKRNED9EJ262581", None, None, Some("3.6L V6 (LLT)")),
+            (
+                "4T1BF1FK5CU000000",
+                Some("Camry"),
+                Some("Sedan"),
+                Some("2.5L I4 (2AR-FE)"),
+            ),
+            ("3VWDX7AJ2BM339496", Some("Jetta"), None, None),
+            ("WVWZZZ1JZ3W000000", Some("Golf IV"), None, None),
+        ];
+
+        for (vin, model, body_style, engine) in cases {
+            let vds = vin_vds_at(vin, 2024).unwrap();
+            assert_eq!(vds.model.as_deref(), model, "{}", vin);
+            assert_eq!(vds.body_style.as_deref(), body_style, "{}", vin);
+            assert_eq!(vds.engine.as_deref(), engine, "{}", vin);
+        }
+    }
+
+    #[test]
+    fn test_vin_vds_years() {
+        // Ford reuses engine code P: 2.0L Zetec until 2004, 2.7L EcoBoost since 2015
+        let decoder = TableDecoder::from_csv(FORD_DATA).unwrap();
+        assert_eq!(
+            decoder
+                .decode("1FTEW1EP5FFA00000", Some(2015))
+                .engine
+                .as_deref(),
+            Some("2.7L V6 EcoBoost")
+        );
+        assert_eq!(decoder.decode("1FTEW1EP5FFA00000", Some(2008)).engine, None);
+        assert_eq!(decoder.decode("1FTEW1EP5FFA00000", None).model, None);
+    }
+
+    #[test]
+    fn test_vin_vds_unknown() {
+        assert!(vin_vds("").is_none());
+        assert!(vin_vds("KNMCSHLMS6P600875").is_none());
+        assert!(vin_vds("G1ND52F14M700000").is_none());
+    }
+
+    #[test]
+    fn test_table_errors() {
+        let header = "Prefix, Field, Positions, Code, Value, Years\n";
+        for line in [
+            ", model, 0, A, X,",
+            ", model, 17-18, AB, X,",
+            ", model, 4-5, A, X,",
+            ", model, 4, A, X, soon",
+            ", model, 4",
+        ] {
+            assert!(
+                TableDecoder::from_csv(&format!("{}{}\n", header, line)).is_err(),
+                "{}",
+                line
+            );
+        }
+    }
+
+    #[test]
+    fn test_registry_precedence() {
+        let table = |model: &str| {
+            let data = format!(
+                "Prefix, Field, Positions, Code, Value, Years\n, model, 4, A, {},\n",
+                model
+            );
+            Arc::new(TableDecoder::from_csv(&data).unwrap())
+        };
+        let mut registry = VdsRegistry::new();
+        registry.register(&["1G"], table("Two"));
+        registry.register(&["1GK"], table("Three"));
+
+        let model = |vin: &str| registry.decode(vin, None).and_then(|vds| vds.model);
+        assert_eq!(model("1GKA0000000000000").as_deref(), Some("Three"));
+        assert_eq!(model("1G1A0000000000000").as_deref(), Some("Two"));
+    }
+}
@@ -38,7 +38,17 @@ use regex::Regex;
 use std::collections::HashMap;
 use std::sync::LazyLock;
 
+use crate::wmi_db::wmi_database;
+
 static WMI_DATA: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "src/wmi.csv"));
+static COUNTRY_DATA: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/",
+    "src/countries.csv"
+));
+
+/// VIN characters in ISO 3780 order, used to compare country code ranges.
+const ISO_3780_ORDER: &str = "ABCDEFGHJKLMNPRSTUVWXYZ1234567890";
 
 static VIN_REGEXES: LazyLock<Vec<Regex>> = LazyLock::new(|| {
     vec![
@@ -65,6 +75,61 @@ static WMI_MAP: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
     map
 });
 
+/// An ISO 3780 range of the first two VIN characters assigned to one country.
+struct CountryRange {
+    first: char,
+    from: usize,
+    to: usize,
+    code: String,
+    name: String,
+}
+
+static COUNTRY_RANGES: LazyLock<Vec<CountryRange>> = LazyLock::new(|| {
+    let mut ranges = Vec::new();
+    let mut reader = ReaderBuilder::new()
+        .delimiter(b',')
+        .trim(csv::Trim::All)
+        .from_reader(COUNTRY_DATA.as_bytes());
+
+    for record in reader.records().flatten() {
+        if record.len() < 4 {
+            continue;
+        }
+
+        let mut from = record[0].chars();
+        let mut to = record[1].chars();
+        if let (Some(first), Some(from), Some(to)) = (from.next(), from.next(), to.nth(1)) {
+            if let (Some(from), Some(to)) = (iso_3780_rank(from), iso_3780_rank(to)) {
+                ranges.push(CountryRange {
+                    first,
+                    from,
+                    to,
+                    code: record[2].to_string(),
+                    name: record[3].to_string(),
+                });
+            }
+        }
+    }
+    ranges
+});
+
+/// Position of a character in the ISO 3780 ordering, if it is a valid VIN character.
+fn iso_3780_rank(c: char) -> Option<usize> {
+    ISO_3780_ORDER.find(c)
+}
+
+/// Looks up the ISO 3166 alpha-2 code and country name of an already cleaned VIN.
+pub(crate) fn country_of(vin: &str) -> Option<(&'static str, &'static str)> {
+    let mut chars = vin.chars();
+    let first = chars.next()?;
+    let second = iso_3780_rank(chars.next()?)?;
+
+    COUNTRY_RANGES
+        .iter()
+        .find(|r| r.first == first && (r.from..=r.to).contains(&second))
+        .map(|r| (r.code.as_str(), r.name.as_str()))
+}
+
 /// Returns a reference to the World Manufacturer Identifier (WMI) lookup table.
 ///
 /// The WMI table maps manufacturer codes to human-readable manufacturer names.
@@ -116,8 +181,11 @@ pub fn get_wmicsv() -> &'static HashMap<String, String> {
 /// // assert_eq!(wmi("939ABCDEFGH123456").unwrap(), "939GH1");
 /// ```
 pub fn wmi(vin: &str) -> Option<String> {
-    let vin = vin_cleaner(vin).unwrap_or_default();
+    wmi_of(&vin_cleaner(vin).unwrap_or_default())
+}
 
+/// Extracts the WMI from an already cleaned VIN.
+pub(crate) fn wmi_of(vin: &str) -> Option<String> {
     // Validate VIN length before any character access
     if vin.len() < 15 {
         return None;
@@ -159,6 +227,9 @@ pub fn wmi(vin: &str) -> Option<String> {
 /// 1. First, tries the first 2 characters of the WMI
 /// 2. Falls back to the full WMI if no match
 ///
+/// Entries from extra WMI files (see [`crate::wmi_db`]) are checked before the
+/// embedded table, most specific WMI first.
+///
 /// # Examples
 ///
 /// ```
@@ -172,26 +243,67 @@ pub fn wmi(vin: &str) -> Option<String> {
 /// assert_eq!(vin_manuf("  1G1ND52F14M712344  ").unwrap(), "General Motors USA");
 /// ```
 pub fn vin_manuf(vin: &str) -> Option<String> {
+    manuf_of(&vin_cleaner(vin).unwrap_or_default())
+}
+
+/// Looks up the manufacturer name of an already cleaned VIN.
+pub(crate) fn manuf_of(vin: &str) -> Option<String> {
+    wmi_of(vin).and_then(|w| wmi_database().manufacturer(&w).map(str::to_string))
+}
+
+/// Extracts the brand from a VIN.
+///
+/// The brand comes from the same WMI entry as [`vin_manuf`]. Brands of the
+/// embedded table are derived from the manufacturer name (e.g. "Audi Hungary"
+/// is an "Audi"); files loaded through [`crate::wmi_db`] can set them explicitly.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (can be dirty/unformatted, will be cleaned)
+///
+/// # Returns
+///
+/// * `Some(String)` - The brand name (e.g. "Audi", "MINI")
+/// * `None` - If the VIN is invalid or the brand is unknown
+///
+/// # Examples
+///
+/// ```
+/// use vin::vin_brand;
+///
+/// assert_eq!(vin_brand("TRUZZZSNZY1063363").unwrap(), "Audi");
+/// assert_eq!(vin_brand("WMWMF7C56ATZ69847").unwrap(), "MINI");
+/// assert!(vin_brand("invalid").is_none());
+/// ```
+pub fn vin_brand(vin: &str) -> Option<String> {
     let vin = vin_cleaner(vin).unwrap_or_default();
+    wmi_of(&vin).and_then(|w| wmi_database().brand(&w).map(str::to_string))
+}
 
-    match wmi(&vin) {
-        Some(w) if !w.is_empty() => {
-            // Safely extract first 2 characters if available
-            if w.len() >= 2 {
-                // Try 2-character WMI first, then fall back to full WMI
-                let chars: Vec<char> = w.chars().collect();
-                let two_char_wmi: String = chars[0..2].iter().collect();
-
-                WMI_MAP
-                    .get(&two_char_wmi)
-                    .or_else(|| WMI_MAP.get(&w))
-                    .cloned()
-            } else {
-                WMI_MAP.get(&w).cloned()
-            }
-        }
-        _ => None,
-    }
+/// Extracts the parent group of the manufacturer from a VIN.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (can be dirty/unformatted, will be cleaned)
+///
+/// # Returns
+///
+/// * `Some(String)` - The group name (e.g. "Volkswagen Group", "General Motors")
+/// * `None` - If the VIN is invalid or the group is unknown
+///
+/// # Examples
+///
+/// ```
+/// use vin::vin_group;
+///
+/// assert_eq!(vin_group("TRUZZZSNZY1063363").unwrap(), "Volkswagen Group");
+/// assert_eq!(vin_group("1GKKRNED9EJ262581").unwrap(), "General Motors");
+/// assert_eq!(vin_group("WMWMF7C56ATZ69847").unwrap(), "BMW Group");
+/// assert!(vin_group("invalid").is_none());
+/// ```
+pub fn vin_group(vin: &str) -> Option<String> {
+    let vin = vin_cleaner(vin).unwrap_or_default();
+    wmi_of(&vin).and_then(|w| wmi_database().group(&w).map(str::to_string))
 }
 
 /// Cleans and validates a VIN string.
@@ -218,6 +330,9 @@ pub fn vin_manuf(vin: &str) -> Option<String> {
 /// 3. 18-character VIN: `[A-HJ-NPR-Z0-9]{18}`
 /// 4. Permissive 17-character VIN: `[A-Z0-9]{17}` (allows I, O, Q)
 ///
+/// These patterns only check the shape of the input. Use [`vin_cleaner_strict`]
+/// to also verify the check digit.
+///
 /// # Examples
 ///
 /// ```
@@ -261,6 +376,208 @@ pub fn vin_cleaner(vin: &str) -> Option<String> {
     None
 }
 
+/// Cleans a VIN string, keeping only candidates that pass check digit validation.
+///
+/// Unlike [`vin_cleaner`], this function never falls back to 16/18-character or
+/// I/O/Q-containing patterns. Only whole words of 17 letters and digits are
+/// candidates, and the first one accepted by [`vin_is_valid`] is returned, so a
+/// VIN glued to extra characters (`1GKKRNED8EJ262581X`) is never cut out of them.
+///
+/// # Arguments
+///
+/// * `vin` - The potentially dirty VIN string
+///
+/// # Returns
+///
+/// * `Some(String)` - A cleaned, uppercase 17-character VIN
+/// * `None` - If no candidate passes validation
+///
+/// # Examples
+///
+/// ```
+/// use vin::vin_cleaner_strict;
+///
+/// assert_eq!(
+///     vin_cleaner_strict("VIN: 1gkkrned9ej262581 (ok)").unwrap(),
+///     "1GKKRNED9EJ262581"
+/// );
+///
+/// // Wrong check digit for a North American VIN
+/// assert!(vin_cleaner_strict("1G1ND52F14M712344").is_none());
+///
+/// // The check digit is not mandatory for European VINs
+/// assert_eq!(
+///     vin_cleaner_strict("WF05XXGCC5FJ00000").unwrap(),
+///     "WF05XXGCC5FJ00000"
+/// );
+///
+/// // I, O and Q are never accepted
+/// assert!(vin_cleaner_strict("1GKKRNED9EJ26258I").is_none());
+///
+/// // No VIN is cut out of a longer word
+/// assert!(vin_cleaner_strict("1GKKRNED8EJ262581X").is_none());
+/// ```
+pub fn vin_cleaner_strict(vin: &str) -> Option<String> {
+    vin.trim()
+        .to_uppercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .find(|candidate| is_valid_of(candidate))
+        .map(str::to_string)
+}
+
+/// Checks whether a string is a valid 17-character VIN.
+///
+/// Returns a string representation of a boolean value ("true" or "false"),
+/// in the same way as the other boolean UDFs of this repository.
+///
+/// # Validation Rules
+///
+/// 1. The whole input (after trimming and uppercasing) must be 17 characters,
+///    made only of digits and letters other than I, O and Q
+/// 2. The last four characters must be digits, as required by ISO 3779 (the
+///    last three for small manufacturers, whose code takes positions 12-14)
+/// 3. If the check digit is mandatory for the VIN's region (see
+///    [`vin_check_digit_required`]), position 9 must match [`vin_check_digit`]
+///
+/// For other regions the check digit is not verified, since many manufacturers
+/// outside North America and China put arbitrary characters in position 9.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (will be trimmed and uppercased)
+///
+/// # Returns
+///
+/// * `Some("true")` - If the input is a valid VIN
+/// * `Some("false")` - Otherwise
+///
+/// Note: This function always returns `Some`, never `None`.
+///
+/// # Examples
+///
+/// ```
+/// use vin::vin_is_valid;
+///
+/// assert_eq!(vin_is_valid("1GKKRNED9EJ262581").unwrap(), "true");
+/// assert_eq!(vin_is_valid("1GKKRNED8EJ262581").unwrap(), "false");
+/// assert_eq!(vin_is_valid("WF05XXGCC5FJ00000").unwrap(), "true");
+/// assert_eq!(vin_is_valid("invalid").unwrap(), "false");
+/// ```
+pub fn vin_is_valid(vin: &str) -> Option<String> {
+    match is_valid_of(&vin.trim().to_uppercase()) {
+        true => Some("true".to_string()),
+        false => Some("false".to_string()),
+    }
+}
+
+/// Returns whether the check digit is mandatory for the region of a VIN.
+///
+/// The check digit is required by FMVSS 115 for vehicles sold in North America
+/// (first character `1`-`5`) and by GB 16735 in China (first character `L`).
+/// Elsewhere ISO 3779 only recommends it, so a mismatch is not an error.
+///
+/// # Examples
+///
+/// ```
+/// use vin::vin_check_digit_required;
+///
+/// assert!(vin_check_digit_required("1GKKRNED9EJ262581"));
+/// assert!(vin_check_digit_required("LVSHCAMB1CE054249"));
+/// assert!(!vin_check_digit_required("WF05XXGCC5FJ00000"));
+/// assert!(!vin_check_digit_required(""));
+/// ```
+pub fn vin_check_digit_required(vin: &str) -> bool {
+    check_digit_required_of(&vin_cleaner(vin).unwrap_or_default())
+}
+
+/// Returns whether the check digit is mandatory for an already cleaned VIN.
+pub(crate) fn check_digit_required_of(vin: &str) -> bool {
+    matches!(vin.chars().next(), Some('1'..='5' | 'L'))
+}
+
+/// Returns whether an already cleaned VIN is a valid 17-character VIN.
+pub(crate) fn is_valid_of(vin: &str) -> bool {
+    let serial_start = if vin.as_bytes().get(2) == Some(&b'9') {
+        14
+    } else {
+        13
+    };
+
+    vin.len() == 17
+        && vin.chars().all(|c| transliterate(c).is_some())
+        && vin[serial_start..].bytes().all(|b| b.is_ascii_digit())
+        && (!check_digit_required_of(vin) || check_digit_valid_of(vin))
+}
+
+/// Computes the expected check digit (position 9) of a VIN.
+///
+/// The check digit is defined by ISO 3779 and FMVSS 115: every character is
+/// transliterated to a number, multiplied by a positional weight, and the sum
+/// modulo 11 gives the digit (`X` stands for 10).
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (will be cleaned first)
+///
+/// # Returns
+///
+/// * `Some(char)` - The expected check digit (`0`-`9` or `X`)
+/// * `None` - If the VIN is not 17 characters after cleaning or contains I, O or Q
+///
+/// # Examples
+///
+/// ```
+/// use vin::vin_check_digit;
+///
+/// assert_eq!(vin_check_digit("1M8GDM9AXKP042788").unwrap(), 'X');
+/// assert_eq!(vin_check_digit("1GKKRNED9EJ262581").unwrap(), '9');
+/// assert!(vin_check_digit("123").is_none());
+/// ```
+pub fn vin_check_digit(vin: &str) -> Option<char> {
+    check_digit_of(&vin_cleaner(vin).unwrap_or_default())
+}
+
+/// Computes the expected check digit of an already cleaned VIN.
+pub(crate) fn check_digit_of(vin: &str) -> Option<char> {
+    const WEIGHTS: [u32; 17] = [8, 7, 6, 5, 4, 3, 2, 10, 0, 9, 8, 7, 6, 5, 4, 3, 2];
+
+    if vin.len() != 17 {
+        return None;
+    }
+
+    let mut sum = 0;
+    for (c, weight) in vin.chars().zip(WEIGHTS) {
+        sum += transliterate(c)? * weight;
+    }
+
+    match sum % 11 {
+        10 => Some('X'),
+        n => char::from_digit(n, 10),
+    }
+}
+
+/// Returns `true` if position 9 of an already cleaned VIN matches its computed check digit.
+pub(crate) fn check_digit_valid_of(vin: &str) -> bool {
+    check_digit_of(vin).is_some_and(|expected| vin.chars().nth(8) == Some(expected))
+}
+
+/// Maps a VIN character to its numeric value for the check digit computation.
+fn transliterate(c: char) -> Option<u32> {
+    match c {
+        '0'..='9' => c.to_digit(10),
+        'A' | 'J' => Some(1),
+        'B' | 'K' | 'S' => Some(2),
+        'C' | 'L' | 'T' => Some(3),
+        'D' | 'M' | 'U' => Some(4),
+        'E' | 'N' | 'V' => Some(5),
+        'F' | 'W' => Some(6),
+        'G' | 'P' | 'X' => Some(7),
+        'H' | 'Y' => Some(8),
+        'R' | 'Z' => Some(9),
+        _ => None,
+    }
+}
+
 /// Determines the continent of origin from a VIN.
 ///
 /// The first character of a VIN indicates the geographic region where
@@ -300,8 +617,11 @@ pub fn vin_cleaner(vin: &str) -> Option<String> {
 /// assert_eq!(vin_continent("8G1ND52F14M712344").unwrap(), "South America");
 /// ```
 pub fn vin_continent(vin: &str) -> Option<&'static str> {
-    let vin = vin_cleaner(vin).unwrap_or_default();
+    continent_of(&vin_cleaner(vin).unwrap_or_default())
+}
 
+/// Maps the first character of an already cleaned VIN to its continent.
+pub(crate) fn continent_of(vin: &str) -> Option<&'static str> {
     // First character of VIN
     let x = vin.chars().next().unwrap_or_default();
 
@@ -316,6 +636,45 @@ pub fn vin_continent(vin: &str) -> Option<&'static str> {
     }
 }
 
+/// Determines the country of origin from a VIN.
+///
+/// The first two characters of a VIN identify the country where the vehicle was
+/// manufactured, as ranges of the ISO 3780 character order
+/// (`A`-`Z` without I, O and Q, then `1`-`9`, then `0`). The table is embedded
+/// from `src/countries.csv` at compile time, so no network access is needed.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (will be cleaned first)
+///
+/// # Returns
+///
+/// * `Some((code, name))` - The ISO 3166 alpha-2 code and the country name
+/// * `None` - If the VIN is invalid or its first two characters are not assigned
+///
+/// # Examples
+///
+/// | Characters | Country        |
+/// |------------|----------------|
+/// | 1A-10      | United States  |
+/// | 2A-20      | Canada         |
+/// | 3A-3W      | Mexico         |
+/// | SA-SM      | United Kingdom |
+/// | WA-W0      | Germany        |
+///
+/// ```
+/// use vin::vin_country;
+///
+/// assert_eq!(vin_country("1G1ND52F14M712344").unwrap(), ("US", "United States"));
+/// assert_eq!(vin_country("3VWDX7AJ2BM339496").unwrap(), ("MX", "Mexico"));
+/// assert_eq!(vin_country("SALGA2HF0EA000000").unwrap(), ("GB", "United Kingdom"));
+/// assert_eq!(vin_country("WF05XXGCC5FJ00000").unwrap(), ("DE", "Germany"));
+/// assert!(vin_country("invalid").is_none());
+/// ```
+pub fn vin_country(vin: &str) -> Option<(&'static str, &'static str)> {
+    country_of(&vin_cleaner(vin).unwrap_or_default())
+}
+
 /// Extracts the model year from a VIN.
 ///
 /// The model year is encoded at different positions depending on the region:
@@ -369,8 +728,155 @@ pub fn vin_continent(vin: &str) -> Option<&'static str> {
 /// assert!(vin_year("123").is_none());
 /// ```
 pub fn vin_year(vin: &str) -> Option<String> {
+    year_of(&vin_cleaner(vin).unwrap_or_default()).map(|y| y.to_string())
+}
+
+/// Extracts the model year from a VIN relative to an explicit reference year.
+///
+/// Unlike [`vin_year`], the result does not depend on the current date or the
+/// server timezone, so decoding the same rows again (e.g. in a backfill) always
+/// gives the same years. The reference year is usually the year of a listing or
+/// registration date: a decoded year more than 1 year after it is moved 30 years
+/// back.
+///
+/// # Position 7 Rule
+///
+/// The model year character repeats every 30 years (`A` is 1980, 2010, 2040...):
+///
+/// - North America (first character `1`-`5`): position 10 holds the year. Since
+///   model year 2010, position 7 is a letter for the 2010-2039 cycle and a digit
+///   for the 1980-2009 cycle (49 CFR 565.15)
+/// - Europe (first character `S`-`Z`): position 11 is read, as in [`vin_year`];
+///   there is no position 7 rule, the 2010-2039 cycle is assumed
+/// - Other regions: position 10 holds the year, the 2010-2039 cycle is assumed
+///
+/// Use [`vin_year_candidates`] to get every possible year when the cycle is not
+/// fixed by position 7.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (must be exactly 17 characters after cleaning)
+/// * `reference_year` - The latest plausible model year minus 1
+///
+/// # Returns
+///
+/// * `Some(String)` - The 4-digit model year
+/// * `None` - If the VIN is invalid, not 17 characters, or year cannot be determined
+///
+/// # Examples
+///
+/// ```
+/// use vin::vin_year_at;
+///
+/// // The cycle of a Japanese VIN depends on the reference year
+/// assert_eq!(vin_year_at("JM1BL1M72C1587426", 2024).unwrap(), "2012");
+/// assert_eq!(vin_year_at("JM1BL1M72C1587426", 2005).unwrap(), "1982");
+///
+/// // Position 7 is numeric: 1980-2009 cycle
+/// assert_eq!(vin_year_at("1FAFP34P63W132895", 2024).unwrap(), "2003");
+///
+/// assert!(vin_year_at("123", 2024).is_none());
+/// ```
+pub fn vin_year_at(vin: &str, reference_year: i32) -> Option<String> {
+    year_at_of(&vin_cleaner(vin).unwrap_or_default(), reference_year).map(|y| y.to_string())
+}
+
+/// Lists every plausible model year of a VIN up to an explicit reference year.
+///
+/// For North American VINs position 7 fixes the 30-year cycle, so there is a
+/// single candidate, the one returned by [`vin_year_at`]. Since the position 7
+/// rule only binds from model year 2010, both cycles are returned when the one of
+/// position 7 is after `reference_year + 1`. For other regions every year of the
+/// 1980-2009, 2010-2039 and 2040-2069 cycles up to `reference_year + 1` is
+/// returned, in ascending order.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (must be exactly 17 characters after cleaning)
+/// * `reference_year` - The latest plausible model year minus 1
+///
+/// # Returns
+///
+/// The candidate years, or an empty list if the year cannot be decoded.
+///
+/// # Examples
+///
+/// ```
+/// use vin::vin_year_candidates;
+///
+/// assert_eq!(vin_year_candidates("JM1BL1M72C1587426", 2024), vec![1982, 2012]);
+/// assert_eq!(vin_year_candidates("JM1BL1M72C1587426", 2005), vec![1982]);
+/// assert_eq!(vin_year_candidates("1GKKRNED9EJ262581", 2024), vec![2014]);
+/// assert_eq!(vin_year_candidates("1GKKRNED9WJ262581", 2024), vec![1998, 2028]);
+/// assert!(vin_year_candidates("123", 2024).is_empty());
+/// ```
+pub fn vin_year_candidates(vin: &str, reference_year: i32) -> Vec<i32> {
+    year_candidates_of(&vin_cleaner(vin).unwrap_or_default(), reference_year)
+}
+
+/// Parses the reference year argument of the `vinYearAt` and `vinYearCandidates` UDFs.
+///
+/// Accepts a year (`2019`) or anything starting with one, such as a ClickHouse
+/// `Date` or `DateTime` (`2019-05-01 10:00:00`).
+///
+/// # Examples
+///
+/// ```
+/// use vin::vin::reference_year;
+///
+/// assert_eq!(reference_year("2019"), Some(2019));
+/// assert_eq!(reference_year(" 2019-05-01 10:00:00"), Some(2019));
+/// assert!(reference_year("19").is_none());
+/// assert!(reference_year("").is_none());
+/// ```
+pub fn reference_year(s: &str) -> Option<i32> {
+    let s = s.trim();
+    let year = s
+        .get(..4)
+        .filter(|y| y.chars().all(|c| c.is_ascii_digit()))?;
+
+    match s[4..].chars().next() {
+        Some(c) if c.is_ascii_digit() => None,
+        _ => year.parse().ok(),
+    }
+}
+
+/// Decodes the model year of an already cleaned VIN.
+pub(crate) fn year_of(vin: &str) -> Option<i32> {
+    year_at_of(vin, Local::now().year())
+}
+
+/// Decodes the model year of an already cleaned VIN relative to a reference year.
+pub(crate) fn year_at_of(vin: &str, reference_year: i32) -> Option<i32> {
+    let (year, _) = year_cycle_of(vin)?;
+
+    // Check for model years in the future
+    Some(if year > reference_year + 1 {
+        year - 30
+    } else {
+        year
+    })
+}
+
+/// Lists the possible model years of an already cleaned VIN up to a reference year.
+pub(crate) fn year_candidates_of(vin: &str, reference_year: i32) -> Vec<i32> {
+    match year_cycle_of(vin) {
+        Some((year, true)) if year > reference_year + 1 => vec![year - 30, year],
+        Some((_, true)) => year_at_of(vin, reference_year).into_iter().collect(),
+        Some((year, false)) => [year - 30, year, year + 30]
+            .into_iter()
+            .filter(|y| *y <= reference_year + 1)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Decodes the model year character of an already cleaned VIN.
+///
+/// Returns the year in the cycle selected by position 7 for North American VINs,
+/// or in the 2010-2039 cycle otherwise, and whether position 7 selected the cycle.
+fn year_cycle_of(vin: &str) -> Option<(i32, bool)> {
     let year_chars = "ABCDEFGHJKLMNPRSTUVWXYZ1234567890".chars();
-    let vin = vin_cleaner(vin).unwrap_or_default();
 
     // Validate VIN length (must be exactly 17 for year extraction)
     if vin.len() != 17 || vin.is_empty() {
@@ -383,8 +889,9 @@ pub fn vin_year(vin: &str) -> Option<String> {
     // This pos 7 check was introduced in US for NA autos and not valid for EU, Asia Cars
     let year_ch = year_chars.filter(|&c| c != 'U' && c != 'Z' && c != '0');
 
-    let is_north_america = vin_continent(&vin).unwrap_or_default() == "North America";
-    let is_eu = vin_continent(&vin).unwrap_or_default() == "Europe";
+    let continent = continent_of(vin).unwrap_or_default();
+    let is_north_america = continent == "North America";
+    let is_eu = continent == "Europe";
 
     // Define possible model year ranges based on VIN type
     let years = if is_north_america {
@@ -409,20 +916,10 @@ pub fn vin_year(vin: &str) -> Option<String> {
         *vin_chars.get(9)?
     };
 
-    let current_year = Local::now().year();
-
-    for (c, y) in year_ch.zip(years) {
-        if c == year_model_char {
-            // Check for model years in the future
-            return Some(if y > current_year + 1 {
-                (y - 30).to_string()
-            } else {
-                y.to_string()
-            });
-        }
-    }
-
-    None
+    year_ch
+        .zip(years)
+        .find(|(c, _)| *c == year_model_char)
+        .map(|(_, y)| (y, is_north_america))
 }
 
 #[cfg(test)]
@@ -589,6 +1086,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_vin_year_at() {
+        // Same result as vin_year for a reference year in the current cycle
+        for v in [
+            "1GKKRNED9EJ262581",
+            "1FAFP34P63W132895",
+            "TRUZZZSNZY1063363",
+        ] {
+            assert_eq!(vin_year_at(v, Local::now().year()), vin_year(v), "{}", v);
+        }
+
+        // Does not depend on the current date
+        assert_eq!(vin_year_at("KNMCSHLMS6P600875", 2005).unwrap(), "2006");
+        assert_eq!(vin_year_at("KNMCSHLMS6P600875", 2035).unwrap(), "2036");
+        assert_eq!(vin_year_at("JTDKN3DU9F0421684", 2014).unwrap(), "2015");
+        assert_eq!(vin_year_at("JTDKN3DU9F0421684", 2013).unwrap(), "1985");
+
+        assert!(vin_year_at("", 2024).is_none());
+    }
+
+    #[test]
+    fn test_vin_year_candidates() {
+        assert_eq!(
+            vin_year_candidates("JTDKN3DU9F0421684", 2024),
+            vec![1985, 2015]
+        );
+        assert_eq!(
+            vin_year_candidates("JTDKN3DU9F0421684", 2050),
+            vec![1985, 2015, 2045]
+        );
+        assert_eq!(vin_year_candidates("1FAFP34P63W132895", 2024), vec![2003]);
+        assert!(vin_year_candidates("1FAFP34P63W132895", 2024)
+            .iter()
+            .all(|y| vin_year_at("1FAFP34P63W132895", 2024) == Some(y.to_string())));
+
+        // A letter in position 7 before 2010, when the rule did not bind yet
+        assert_eq!(vin_year_at("1GKKRNED9WJ262581", 2024).unwrap(), "1998");
+        assert_eq!(
+            vin_year_candidates("1GKKRNED9WJ262581", 2024),
+            vec![1998, 2028]
+        );
+        assert_eq!(vin_year_candidates("1GKKRNED9WJ262581", 2027), vec![2028]);
+    }
+
+    #[test]
+    fn test_reference_year() {
+        assert_eq!(reference_year("2019-05-01"), Some(2019));
+        assert_eq!(reference_year("2019"), Some(2019));
+        assert!(reference_year("20190").is_none());
+        assert!(reference_year("abcd").is_none());
+        assert!(reference_year("+201").is_none());
+    }
+
     #[test]
     fn test_vin_continent() {
         assert_eq!(vin_continent("1G1ND52F14M712344").unwrap(), "North America");
@@ -609,6 +1159,55 @@ mod tests {
         assert_eq!(vin_continent("JTFHX02PX0000000000 ... ").unwrap(), "Asia");
     }
 
+    #[test]
+    fn test_vin_country() {
+        let vinl = [
+            "1GKKRNED9EJ262581",
+            "2A4GP54L16R805929",
+            "3C3CFFAR2CT212308",
+            "5LMJJ3J57EEL08671",
+            "JM1BL1M72C1587426",
+            "KNMCSHLMS6P600875",
+            "LVSHCAMB1CE054249",
+            "TRUZZZSNZY1063363",
+            "Y7A6135B660801530",
+            "VF1RFB00X56000000",
+            "ZFA31200000000000",
+            "9BWZZZ377VT004251",
+            "93HGD17503Z000000",
+        ];
+        let resl = [
+            "US", "CA", "MX", "US", "JP", "KR", "CN", "HU", "UA", "FR", "IT", "BR", "BR",
+        ];
+
+        for (v, r) in vinl.iter().zip(resl.iter()) {
+            assert_eq!(
+                vin_country(v).map(|(code, _)| code).unwrap_or_default(),
+                *r,
+                "checking if vin_country({}) == {}",
+                v,
+                r
+            );
+        }
+    }
+
+    #[test]
+    fn test_vin_country_ranges() {
+        // Ranges crossing from letters into digits
+        assert_eq!(vin_country("3X000000000000000").unwrap().1, "Costa Rica");
+        assert_eq!(vin_country("37000000000000000").unwrap().1, "Costa Rica");
+        assert_eq!(
+            vin_country("38000000000000000").unwrap().1,
+            "Cayman Islands"
+        );
+        assert_eq!(vin_country("S1000000000000000").unwrap().1, "Latvia");
+
+        // Unassigned ranges
+        assert!(vin_country("AR000000000000000").is_none());
+        assert!(vin_country("ZS000000000000000").is_none());
+        assert!(vin_country("").is_none());
+    }
+
     #[test]
     fn test_vin_cleaner() {
         assert_eq!(
@@ -678,6 +1277,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_vin_check_digit() {
+        let vinl = [
+            "1GKKRNED9EJ262581",
+            "2A4GP54L16R805929",
+            "JM1BL1M72C1587426",
+            "1FTEW1CM9BFA74557",
+            "1FAFP34P63W132895",
+            "1J4GL48K05W616251",
+            "3VWDX7AJ2BM339496",
+            "5LMJJ3J57EEL08671",
+            "WMWMF7C56ATZ69847",
+            "JTDKN3DU9F0421684",
+            "3C3CFFAR2CT212308",
+            "1M8GDM9AXKP042788",
+        ];
+
+        for v in vinl {
+            assert_eq!(
+                vin_check_digit(v),
+                v.chars().nth(8),
+                "checking vin_check_digit({})",
+                v
+            );
+        }
+
+        assert_eq!(vin_check_digit("1G1ND52F14M712344").unwrap(), '7');
+        assert!(vin_check_digit("1G1ND52F14M71234").is_none());
+        assert!(vin_check_digit("").is_none());
+    }
+
+    #[test]
+    fn test_vin_is_valid() {
+        assert_eq!(vin_is_valid("1GKKRNED9EJ262581").unwrap(), "true");
+        assert_eq!(vin_is_valid(" 1gkkrned9ej262581 ").unwrap(), "true");
+        assert_eq!(vin_is_valid("JTDKN3DU9F0421684").unwrap(), "true");
+
+        // Wrong check digit in North America
+        assert_eq!(vin_is_valid("1G1ND52F14M712344").unwrap(), "false");
+        assert_eq!(vin_is_valid("1GKKRNED8EJ262581").unwrap(), "false");
+
+        // Check digit not mandatory outside North America and China
+        assert_eq!(vin_is_valid("TRUZZZSNZY1063363").unwrap(), "true");
+        assert_eq!(vin_is_valid("KNMCSHLMS6P600875").unwrap(), "true");
+
+        // I, O and Q are never valid
+        assert_eq!(vin_is_valid("WF05XXGCC5FJ0000O").unwrap(), "false");
+
+        assert_eq!(vin_is_valid("1GKKRNED9EJ26258").unwrap(), "false");
+        assert_eq!(vin_is_valid("").unwrap(), "false");
+
+        // The whole input is validated, not a part of it
+        assert_eq!(vin_is_valid("1GKKRNED8EJ262581X").unwrap(), "false");
+        assert_eq!(vin_is_valid("X1GKKRNED8EJ262581").unwrap(), "false");
+        assert_eq!(vin_is_valid("VIN 1GKKRNED9EJ262581").unwrap(), "false");
+
+        // The last four characters of a VIN are digits
+        assert_eq!(vin_is_valid("ABCDEFGHJKLMNPRST").unwrap(), "false");
+        assert_eq!(vin_is_valid("WA9ZZZ12345ABC123").unwrap(), "true");
+        assert_eq!(vin_is_valid("WA1ZZZ12345ABC123").unwrap(), "false");
+    }
+
+    #[test]
+    fn test_vin_cleaner_strict() {
+        assert_eq!(
+            vin_cleaner_strict("JTDKN3DU9F0421684 (ijdfhsdfs)").unwrap(),
+            "JTDKN3DU9F0421684"
+        );
+
+        // VINs are never cut out of longer words
+        assert!(vin_cleaner_strict("11GKKRNED9EJ262581").is_none());
+        assert!(vin_cleaner_strict("1GKKRNED8EJ262581X").is_none());
+        assert!(vin_cleaner_strict("X1GKKRNED8EJ262581").is_none());
+        assert!(vin_cleaner_strict("ABCDEFGHJKLMNPRST").is_none());
+        assert_eq!(
+            vin_cleaner_strict("1GKKRNED8EJ262581/1GKKRNED9EJ262581").unwrap(),
+            "1GKKRNED9EJ262581"
+        );
+
+        // Permissive matches of vin_cleaner are rejected
+        assert_eq!(
+            vin_cleaner("1G1ND52FO4M712344").unwrap(),
+            "1G1ND52FO4M712344"
+        );
+        assert!(vin_cleaner_strict("1G1ND52FO4M712344").is_none());
+        assert!(vin_cleaner_strict("G1ND52F14M700000").is_none());
+        assert!(vin_cleaner_strict("").is_none());
+    }
+
     #[test]
     fn test_vin_cleaner_bad_input() {
         assert!(vin_cleaner("123").is_none());
@@ -0,0 +1,662 @@
+//! Offline VIN decoding with a snapshot of the NHTSA vPIC database.
+//!
+//! NHTSA publishes the vPIC (Product Information Catalog) database as a SQL
+//! Server backup. The `vin-vpic-convert` tool turns an export of it into a
+//! compact binary index, which the `vin-decode-vpic` UDF loads at startup. No
+//! network access is needed at query time, and the embedded WMI table is used
+//! as a fallback for manufacturers missing from the snapshot.
+//!
+//! # Building an Index
+//!
+//! Restore the vPIC backup and export the patterns as CSV with this query:
+//!
+//! ```sql
+//! SELECT w.Wmi AS WMI, wvs.YearFrom, wvs.YearTo, p.Keys, e.Name AS Element,
+//!        COALESCE(mo.Name, bc.Name, ft.Name, c.Name, p.AttributeId) AS Value
+//! FROM Pattern p
+//! JOIN Element e ON e.Id = p.ElementId
+//! JOIN Wmi_VinSchema wvs ON wvs.VinSchemaId = p.VinSchemaId
+//! JOIN Wmi w ON w.Id = wvs.WmiId
+//! LEFT JOIN Model mo ON e.Name = 'Model' AND mo.Id = TRY_CAST(p.AttributeId AS int)
+//! LEFT JOIN BodyStyle bc ON e.Name = 'Body Class' AND bc.Id = TRY_CAST(p.AttributeId AS int)
+//! LEFT JOIN FuelType ft ON e.Name = 'Fuel Type - Primary' AND ft.Id = TRY_CAST(p.AttributeId AS int)
+//! LEFT JOIN Country c ON e.Name = 'Plant Country' AND c.Id = TRY_CAST(p.AttributeId AS int)
+//! WHERE e.Name IN ('Model', 'Trim', 'Body Class', 'Fuel Type - Primary', 'Plant City', 'Plant Country')
+//! UNION ALL
+//! SELECT w.Wmi, NULL, NULL, '*', 'Make', m.Name
+//! FROM Wmi w JOIN Wmi_Make wm ON wm.WmiId = w.Id JOIN Make m ON m.Id = wm.MakeId
+//! ```
+//!
+//! Then convert the CSV and point the UDF to the index, either with the
+//! `VIN_VPIC_PATH` environment variable or as an argument:
+//!
+//! ```text
+//! vin-vpic-convert vpic.csv /var/lib/clickhouse/user_scripts/vpic.bin
+//! <command>vin-decode-vpic /var/lib/clickhouse/user_scripts/vpic.bin</command>
+//! ```
+//!
+//! # Pattern Matching
+//!
+//! vPIC keys are matched against positions 4-8 and 10-17 of the VIN, separated
+//! by `|` (e.g. `TEW1C|*B`). `*` matches any character and `[A-C]` a class of
+//! characters; shorter keys match a prefix. When several patterns of the model
+//! year match, the one with the most literal characters wins.
+
+use anyhow::{bail, Context, Result};
+use csv::ReaderBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::vin::{vin_cleaner, wmi_of, year_of};
+use crate::wmi_db::wmi_database;
+
+/// Environment variable with the path of the vPIC index to load.
+pub const VPIC_PATH_ENV: &str = "VIN_VPIC_PATH";
+
+/// Header of an index file, followed by the postcard-encoded [`VpicIndex`].
+const MAGIC: &[u8; 8] = b"VINVPIC2";
+
+static VPIC_INDEX: OnceLock<Option<VpicIndex>> = OnceLock::new();
+
+/// vPIC elements kept in the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+enum Element {
+    Make,
+    Model,
+    Trim,
+    BodyClass,
+    FuelType,
+    PlantCity,
+    PlantCountry,
+}
+
+impl Element {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Make" => Some(Element::Make),
+            "Model" => Some(Element::Model),
+            "Trim" => Some(Element::Trim),
+            "Body Class" => Some(Element::BodyClass),
+            "Fuel Type - Primary" => Some(Element::FuelType),
+            "Plant City" => Some(Element::PlantCity),
+            "Plant Country" => Some(Element::PlantCountry),
+            _ => None,
+        }
+    }
+}
+
+/// One vPIC pattern, with its value stored in the string table.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Pattern {
+    keys: String,
+    element: Element,
+    value: u32,
+    year_from: Option<i32>,
+    year_to: Option<i32>,
+}
+
+impl Pattern {
+    fn applies_to(&self, model_year: Option<i32>) -> bool {
+        let Some(year) = model_year else {
+            return true;
+        };
+
+        self.year_from.is_none_or(|from| year >= from) && self.year_to.is_none_or(|to| year <= to)
+    }
+}
+
+/// Vehicle attributes decoded from a vPIC snapshot.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct VpicInfo {
+    /// Make, e.g. "FORD".
+    pub make: Option<String>,
+    /// Model, e.g. "F-150".
+    pub model: Option<String>,
+    /// Trim level, e.g. "XLT".
+    pub trim: Option<String>,
+    /// Body class, e.g. "Pickup".
+    pub body_class: Option<String>,
+    /// Primary fuel type, e.g. "Gasoline".
+    pub fuel_type: Option<String>,
+    /// Assembly plant as "city, country".
+    pub plant: Option<String>,
+}
+
+/// A compact index of vPIC patterns, keyed by WMI.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VpicIndex {
+    strings: Vec<String>,
+    patterns: HashMap<String, Vec<Pattern>>,
+}
+
+impl VpicIndex {
+    /// Builds an index from a CSV export of vPIC patterns.
+    ///
+    /// The CSV needs a header with the `WMI`, `YearFrom`, `YearTo`, `Keys`,
+    /// `Element` and `Value` columns (see the [module documentation](self)).
+    /// Rows of other elements or with an empty value are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vin::vpic::VpicIndex;
+    ///
+    /// let csv = "WMI,YearFrom,YearTo,Keys,Element,Value\n\
+    ///            1FT,,,*,Make,FORD\n\
+    ///            1FT,2011,2014,*W1,Model,F-150\n";
+    /// let index = VpicIndex::from_csv(csv.as_bytes()).unwrap();
+    /// assert_eq!(index.len(), 2);
+    /// ```
+    pub fn from_csv<R: Read>(reader: R) -> Result<Self> {
+        let mut reader = ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(reader);
+
+        let headers = reader.headers()?.clone();
+        let column = |name: &str| {
+            headers
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(name))
+                .with_context(|| format!("CSV header must contain a {} column", name))
+        };
+        let columns = [
+            column("WMI")?,
+            column("YearFrom")?,
+            column("YearTo")?,
+            column("Keys")?,
+            column("Element")?,
+            column("Value")?,
+        ];
+
+        let mut index = VpicIndex::default();
+        let mut values: HashMap<String, u32> = HashMap::new();
+
+        for (i, record) in reader.records().enumerate() {
+            let record = record.with_context(|| format!("Invalid CSV line {}", i + 2))?;
+            let [wmi, year_from, year_to, keys, element, value] =
+                columns.map(|c| record.get(c).unwrap_or_default());
+
+            let Some(element) = Element::from_name(element) else {
+                continue;
+            };
+            if wmi.is_empty() || value.is_empty() || value.eq_ignore_ascii_case("NULL") {
+                continue;
+            }
+
+            let year = |s: &str| -> Result<Option<i32>> {
+                match s {
+                    "" | "NULL" => Ok(None),
+                    s => s
+                        .parse()
+                        .map(Some)
+                        .with_context(|| format!("Invalid year on CSV line {}: {}", i + 2, s)),
+                }
+            };
+            let pattern = Pattern {
+                keys: keys.to_uppercase(),
+                element,
+                value: index.intern(&mut values, value),
+                year_from: year(year_from)?,
+                year_to: year(year_to)?,
+            };
+
+            index
+                .patterns
+                .entry(wmi.to_uppercase())
+                .or_default()
+                .push(pattern);
+        }
+
+        Ok(index)
+    }
+
+    /// Writes the index in the binary format read by [`VpicIndex::read_from`].
+    pub fn write_to<W: Write>(&self, writer: W) -> Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writer.write_all(MAGIC)?;
+        postcard::to_io(self, &mut writer)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Reads an index written by [`VpicIndex::write_to`].
+    ///
+    /// Truncated or corrupt indexes, and indexes written by an older version of
+    /// `vin-vpic-convert`, are rejected with an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vin::vpic::VpicIndex;
+    ///
+    /// let csv = "WMI,YearFrom,YearTo,Keys,Element,Value\n1FT,,,*,Make,FORD\n";
+    /// let mut bytes = Vec::new();
+    /// VpicIndex::from_csv(csv.as_bytes()).unwrap().write_to(&mut bytes).unwrap();
+    ///
+    /// let index = VpicIndex::read_from(bytes.as_slice()).unwrap();
+    /// assert_eq!(index.len(), 1);
+    ///
+    /// assert!(VpicIndex::read_from(&b"not an index"[..]).is_err());
+    /// ```
+    pub fn read_from<R: Read>(reader: R) -> Result<Self> {
+        let mut reader = BufReader::new(reader);
+        let mut magic = [0; MAGIC.len()];
+        reader
+            .read_exact(&mut magic)
+            .context("Not a vPIC index file")?;
+        if &magic != MAGIC {
+            bail!("Not a vPIC index file");
+        }
+
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let index: VpicIndex = postcard::from_bytes(&bytes).context("Corrupt vPIC index")?;
+
+        let strings = index.strings.len();
+        if let Some(pattern) = index
+            .patterns
+            .values()
+            .flatten()
+            .find(|pattern| pattern.value as usize >= strings)
+        {
+            bail!(
+                "Corrupt vPIC index: value {} of {} strings",
+                pattern.value,
+                strings
+            );
+        }
+
+        Ok(index)
+    }
+
+    /// Loads an index file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open vPIC index: {}", path.display()))?;
+
+        Self::read_from(file)
+            .with_context(|| format!("Failed to read vPIC index: {}", path.display()))
+    }
+
+    /// Returns the number of patterns in the index.
+    pub fn len(&self) -> usize {
+        self.patterns.values().map(Vec::len).sum()
+    }
+
+    /// Returns `true` if the index has no patterns.
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Decodes an already cleaned 17-character VIN.
+    ///
+    /// Patterns restricted to model years are skipped when `model_year` does not
+    /// fall within them; all patterns are considered when it is `None`.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the VIN is not 17 ASCII characters long or its WMI is not in the index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vin::vpic::VpicIndex;
+    ///
+    /// let csv = "WMI,YearFrom,YearTo,Keys,Element,Value\n\
+    ///            1FT,,,*,Make,FORD\n\
+    ///            1FT,2011,2014,*W1,Model,F-150\n\
+    ///            1FT,2011,2014,*W1C[K-N]|B,Body Class,Pickup\n";
+    /// let index = VpicIndex::from_csv(csv.as_bytes()).unwrap();
+    ///
+    /// let info = index.decode("1FTEW1CM9BFA74557", Some(2011)).unwrap();
+    /// assert_eq!(info.make.as_deref(), Some("FORD"));
+    /// assert_eq!(info.model.as_deref(), Some("F-150"));
+    /// assert_eq!(info.body_class.as_deref(), Some("Pickup"));
+    ///
+    /// assert_eq!(index.decode("1FTEW1CM9BFA74557", Some(2020)).unwrap().model, None);
+    /// assert!(index.decode("1GKKRNED9EJ262581", Some(2014)).is_none());
+    /// ```
+    pub fn decode(&self, vin: &str, model_year: Option<i32>) -> Option<VpicInfo> {
+        if vin.len() != 17 || !vin.is_ascii() {
+            return None;
+        }
+
+        let wmi = wmi_of(vin)?;
+        let patterns = self
+            .patterns
+            .get(&wmi)
+            .or_else(|| self.patterns.get(&vin[..3]))?;
+        let target = format!("{}|{}", &vin[3..8], &vin[9..]);
+
+        let mut values: HashMap<Element, (usize, &str)> = HashMap::new();
+        for pattern in patterns {
+            if !pattern.applies_to(model_year) {
+                continue;
+            }
+            let Some(score) = key_score(&pattern.keys, &target) else {
+                continue;
+            };
+
+            let value = self.strings[pattern.value as usize].as_str();
+            values
+                .entry(pattern.element)
+                .and_modify(|best| {
+                    if score > best.0 {
+                        *best = (score, value);
+                    }
+                })
+                .or_insert((score, value));
+        }
+
+        let value = |element: Element| values.get(&element).map(|(_, v)| v.to_string());
+        let plant = [Element::PlantCity, Element::PlantCountry]
+            .into_iter()
+            .filter_map(value)
+            .collect::<Vec<_>>();
+
+        Some(VpicInfo {
+            make: value(Element::Make),
+            model: value(Element::Model),
+            trim: value(Element::Trim),
+            body_class: value(Element::BodyClass),
+            fuel_type: value(Element::FuelType),
+            plant: (!plant.is_empty()).then(|| plant.join(", ")),
+        })
+    }
+
+    /// Returns the position of a value in the string table, adding it if needed.
+    fn intern(&mut self, values: &mut HashMap<String, u32>, value: &str) -> u32 {
+        *values.entry(value.to_string()).or_insert_with(|| {
+            self.strings.push(value.to_string());
+            (self.strings.len() - 1) as u32
+        })
+    }
+}
+
+/// Matches a vPIC key against the start of `target`.
+///
+/// Returns the number of literal characters of the key, or `None` if it does not match.
+fn key_score(key: &str, target: &str) -> Option<usize> {
+    let mut key = key.chars();
+    let mut target = target.chars();
+    let mut score = 0;
+
+    while let Some(k) = key.next() {
+        let t = target.next()?;
+
+        match k {
+            '*' => {}
+            '[' => {
+                let class: Vec<char> = key.by_ref().take_while(|&c| c != ']').collect();
+                if !class_matches(&class, t) {
+                    return None;
+                }
+            }
+            _ if k == t => score += 1,
+            _ => return None,
+        }
+    }
+
+    Some(score)
+}
+
+/// Returns `true` if `c` belongs to a character class such as `A-C0-9X`.
+fn class_matches(class: &[char], c: char) -> bool {
+    let mut i = 0;
+
+    while i < class.len() {
+        if i + 2 < class.len() && class[i + 1] == '-' {
+            if (class[i]..=class[i + 2]).contains(&c) {
+                return true;
+            }
+            i += 3;
+        } else {
+            if class[i] == c {
+                return true;
+            }
+            i += 1;
+        }
+    }
+
+    false
+}
+
+/// A VIN decoded with the vPIC index, or with the WMI table as a fallback.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VpicRecord {
+    /// The cleaned VIN.
+    pub vin: String,
+    /// Decoded model year.
+    pub model_year: Option<i32>,
+    /// Decoded vehicle attributes.
+    #[serde(flatten)]
+    pub info: VpicInfo,
+    /// Where the attributes come from: `vpic`, `wmi` or `null` if unknown.
+    pub source: Option<&'static str>,
+}
+
+/// Initializes the global vPIC index from a file, or from `VIN_VPIC_PATH`.
+///
+/// Without a path and without the environment variable, no index is used and
+/// [`vin_decode_vpic`] only falls back to the WMI table. This must be called
+/// before the first lookup; calling it again afterwards returns an error.
+pub fn init_vpic_index<P: AsRef<Path>>(path: Option<P>) -> Result<()> {
+    let index = match path {
+        Some(path) => Some(VpicIndex::load(path)?),
+        None => load_from_env()?,
+    };
+
+    if VPIC_INDEX.set(index).is_err() {
+        bail!("vPIC index is already initialized");
+    }
+
+    Ok(())
+}
+
+/// Returns the global vPIC index, if one is configured.
+///
+/// If [`init_vpic_index`] was not called, the index is loaded from
+/// `VIN_VPIC_PATH`. Errors are logged to stderr and no index is used.
+pub fn vpic_index() -> Option<&'static VpicIndex> {
+    VPIC_INDEX
+        .get_or_init(|| {
+            load_from_env().unwrap_or_else(|e| {
+                eprintln!("ERROR: {:#}", e);
+                None
+            })
+        })
+        .as_ref()
+}
+
+fn load_from_env() -> Result<Option<VpicIndex>> {
+    match std::env::var_os(VPIC_PATH_ENV) {
+        Some(path) if !path.is_empty() => VpicIndex::load(path).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Decodes a VIN with the global vPIC index.
+///
+/// When no index is configured or the WMI is not in it, the make falls back to
+/// the brand (or manufacturer) of the WMI table and `source` is `wmi`.
+///
+/// # Arguments
+///
+/// * `vin` - The VIN string (can be dirty/unformatted, will be cleaned)
+///
+/// # Returns
+///
+/// * `Some(VpicRecord)` - The decoded record
+/// * `None` - If no VIN could be found in the input
+///
+/// # Examples
+///
+/// ```
+/// use vin::vpic::vin_vpic;
+///
+/// // Without VIN_VPIC_PATH, only the WMI table is used
+/// let record = vin_vpic("TRUZZZSNZY1063363").unwrap();
+/// assert_eq!(record.info.make.as_deref(), Some("Audi"));
+/// assert_eq!(record.source, Some("wmi"));
+/// ```
+pub fn vin_vpic(vin: &str) -> Option<VpicRecord> {
+    let vin = vin_cleaner(vin)?;
+    let model_year = year_of(&vin);
+
+    let (info, source) = match vpic_index().and_then(|index| index.decode(&vin, model_year)) {
+        Some(info) => (info, Some("vpic")),
+        None => {
+            let entry = wmi_of(&vin).and_then(|wmi| wmi_database().entry(&wmi));
+            let info = VpicInfo {
+                make: entry.map(|e| e.brand.clone().unwrap_or_else(|| e.manufacturer.clone())),
+                ..VpicInfo::default()
+            };
+            (info, entry.map(|_| "wmi"))
+        }
+    };
+
+    Some(VpicRecord {
+        vin,
+        model_year,
+        info,
+        source,
+    })
+}
+
+/// Decodes a VIN with the global vPIC index into a JSON object.
+///
+/// This is the UDF entry point for `vinDecodeVpic`. The fields are the ones of
+/// [`VpicRecord`]; fields that cannot be decoded are `null`.
+///
+/// # Examples
+///
+/// ```
+/// use vin::vpic::vin_decode_vpic;
+///
+/// let json = vin_decode_vpic("TRUZZZSNZY1063363").unwrap();
+/// assert!(json.contains(r#""make":"Audi""#));
+/// assert!(vin_decode_vpic("").is_none());
+/// ```
+pub fn vin_decode_vpic(vin: &str) -> Option<String> {
+    vin_vpic(vin).and_then(|record| serde_json::to_string(&record).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "WMI,YearFrom,YearTo,Keys,Element,Value,Extra
+1FT,,,*,Make,FORD,
+1FT,2011,2014,*W1,Model,F-150,
+1FT,2011,2014,*W1C,Trim,XL,
+1FT,2011,2014,EW1CM,Trim,XLT,
+1FT,2011,2014,****[A-HJ-NP-Z],Fuel Type - Primary,Gasoline,
+1FT,2011,2014,*****|*[A-F],Plant City,DEARBORN,
+1FT,2011,2014,*****|*[A-F],Plant Country,UNITED STATES (USA),
+1FT,2011,2014,*****|*[A-F],Engine Model,Cyclone,
+1FT,2011,,*****,Body Class,NULL,
+";
+
+    fn index() -> VpicIndex {
+        VpicIndex::from_csv(CSV.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_vpic_decode() {
+        let info = index().decode("1FTEW1CM9BFA74557", Some(2011)).unwrap();
+        assert_eq!(
+            info,
+            VpicInfo {
+                make: Some("FORD".to_string()),
+                model: Some("F-150".to_string()),
+                trim: Some("XLT".to_string()),
+                body_class: None,
+                fuel_type: Some("Gasoline".to_string()),
+                plant: Some("DEARBORN, UNITED STATES (USA)".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_vpic_decode_years() {
+        let index = index();
+        let info = index.decode("1FTEW1CM9BFA74557", Some(2015)).unwrap();
+        assert_eq!(info.make.as_deref(), Some("FORD"));
+        assert_eq!(info.model, None);
+
+        let info = index.decode("1FTEW1CM9BFA74557", None).unwrap();
+        assert_eq!(info.model.as_deref(), Some("F-150"));
+    }
+
+    #[test]
+    fn test_vpic_decode_non_ascii() {
+        let index = index();
+        assert_eq!(index.decode(&format!("1FT{}", "é".repeat(7)), None), None);
+    }
+
+    #[test]
+    fn test_vpic_roundtrip() {
+        let mut bytes = Vec::new();
+        index().write_to(&mut bytes).unwrap();
+
+        let index = VpicIndex::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(index.len(), 7);
+        assert_eq!(
+            index
+                .decode("1FTEW1CM9BFA74557", Some(2012))
+                .unwrap()
+                .trim
+                .as_deref(),
+            Some("XLT")
+        );
+    }
+
+    #[test]
+    fn test_vpic_corrupt() {
+        let mut bytes = Vec::new();
+        index().write_to(&mut bytes).unwrap();
+        assert!(VpicIndex::read_from(&bytes[..bytes.len() - 4]).is_err());
+        assert!(VpicIndex::read_from(&bytes[..MAGIC.len()]).is_err());
+
+        let mut index = index();
+        index.strings.truncate(1);
+        let mut bytes = Vec::new();
+        index.write_to(&mut bytes).unwrap();
+        assert!(VpicIndex::read_from(bytes.as_slice()).is_err());
+    }
+
+    #[test]
+    fn test_vpic_csv_errors() {
+        assert!(VpicIndex::from_csv("WMI,Keys\n1FT,*\n".as_bytes()).is_err());
+        assert!(VpicIndex::from_csv(
+            "WMI,YearFrom,YearTo,Keys,Element,Value\n1FT,soon,,*,Make,FORD\n".as_bytes()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_key_score() {
+        assert_eq!(key_score("*W1", "EW1CM|BFA74557"), Some(2));
+        assert_eq!(key_score("EW1CM|*B", "EW1CM|BFA74557"), None);
+        assert_eq!(key_score("EW1CM|B", "EW1CM|BFA74557"), Some(7));
+        assert_eq!(key_score("[A-E]W", "EW1CM|BFA74557"), Some(1));
+        assert_eq!(key_score("[F-Z]W", "EW1CM|BFA74557"), None);
+        assert_eq!(key_score("EW1CM|BFA745570", "EW1CM|BFA74557"), None);
+    }
+
+    #[test]
+    fn test_vin_vpic_fallback() {
+        let record = vin_vpic("1GKKRNED9EJ262581").unwrap();
+        assert_eq!(record.model_year, Some(2014));
+        assert_eq!(record.info.make.as_deref(), Some("General Motors USA"));
+        assert_eq!(record.source, Some("wmi"));
+
+        assert!(vin_vpic("invalid").is_none());
+    }
+}
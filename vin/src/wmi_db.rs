@@ -0,0 +1,411 @@
+//! World Manufacturer Identifier (WMI) database with runtime overrides.
+//!
+//! The embedded `src/wmi.csv` table is compiled into every binary. Additional
+//! CSV or JSON files can be loaded at startup to add manufacturers or override
+//! embedded entries without rebuilding:
+//!
+//! - From the `VIN_WMI_PATH` environment variable (several paths separated by `:`)
+//! - From command-line arguments of the UDF binaries, e.g.
+//!   `<command>vin-manuf /etc/clickhouse-server/wmi_extra.csv</command>`
+//!
+//! # Precedence
+//!
+//! 1. Files given as arguments override files from `VIN_WMI_PATH`, and a later
+//!    file overrides an earlier one for the same WMI
+//! 2. Entries loaded from files are looked up most specific first: the 6-character
+//!    WMI of small manufacturers (see [`crate::vin::wmi`]), then 3, then 2 characters
+//! 3. If no file entry matches, the embedded table is used with the historical
+//!    lookup order of [`crate::vin::vin_manuf`]: 2 characters, then the full WMI
+//!
+//! # File Formats
+//!
+//! CSV files need a header row. `WMI` and `Manufacturer` columns are required,
+//! `Brand` and `Group` are optional:
+//!
+//! ```text
+//! WMI, Manufacturer, Brand, Group
+//! 7SA, Tesla USA, Tesla, Tesla
+//! 1G1, Chevrolet USA, Chevrolet, General Motors
+//! ```
+//!
+//! JSON files contain an array of objects with the same (lowercase) fields, or an
+//! object mapping WMIs to manufacturer names:
+//!
+//! ```text
+//! [{"wmi": "7SA", "manufacturer": "Tesla USA", "brand": "Tesla"}]
+//! {"7SA": "Tesla USA", "LRW": "Tesla China"}
+//! ```
+//!
+//! When a file does not provide the brand or group, they are derived from the
+//! manufacturer name using the embedded `src/brands.csv` table.
+
+use anyhow::{bail, Context, Result};
+use csv::ReaderBuilder;
+use serde::Deserialize;
+use shared::table::GlobalTable;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use crate::vin::get_wmicsv;
+
+/// Environment variable listing extra WMI files to load.
+pub const WMI_PATH_ENV: &str = "VIN_WMI_PATH";
+
+static BRAND_DATA: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", "src/brands.csv"));
+
+/// Manufacturer name prefixes mapped to their brand and parent group.
+static BRANDS: LazyLock<Vec<(String, Option<String>, String)>> = LazyLock::new(|| {
+    let mut brands = Vec::new();
+    let mut reader = ReaderBuilder::new()
+        .delimiter(b',')
+        .trim(csv::Trim::All)
+        .from_reader(BRAND_DATA.as_bytes());
+
+    for record in reader.records().flatten() {
+        if record.len() >= 3 {
+            let brand = Some(record[1].to_string()).filter(|b| !b.is_empty());
+            brands.push((record[0].to_string(), brand, record[2].to_string()));
+        }
+    }
+
+    // Longest prefix first, so "Volvo Cars" wins over "Volvo"
+    brands.sort_by_key(|b| std::cmp::Reverse(b.0.len()));
+    brands
+});
+
+static WMI_DATABASE: GlobalTable<WmiDatabase> = GlobalTable::new(
+    "WMI database",
+    WMI_PATH_ENV,
+    WmiDatabase::embedded,
+    |db, path| db.load_file(path),
+);
+
+/// Manufacturer information for one WMI.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct WmiEntry {
+    /// Manufacturer name, e.g. "Chevrolet USA".
+    pub manufacturer: String,
+    /// Marketing brand, e.g. "Chevrolet".
+    pub brand: Option<String>,
+    /// Parent company or group, e.g. "General Motors".
+    pub group: Option<String>,
+}
+
+impl WmiEntry {
+    /// Creates an entry, deriving missing brand and group from the manufacturer name.
+    pub fn new(manufacturer: &str, brand: Option<&str>, group: Option<&str>) -> Self {
+        let derived = BRANDS.iter().find(|(prefix, _, _)| {
+            manufacturer
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric()))
+        });
+
+        WmiEntry {
+            manufacturer: manufacturer.trim().to_string(),
+            brand: non_empty(brand)
+                .map(str::to_string)
+                .or_else(|| derived.and_then(|(_, brand, _)| brand.clone())),
+            group: non_empty(group)
+                .map(str::to_string)
+                .or_else(|| derived.map(|(_, _, group)| group.clone())),
+        }
+    }
+}
+
+/// Trims an optional field, treating empty values as missing.
+fn non_empty(s: Option<&str>) -> Option<&str> {
+    s.map(str::trim).filter(|s| !s.is_empty())
+}
+
+/// One object of a JSON WMI file.
+#[derive(Deserialize)]
+struct JsonEntry {
+    wmi: String,
+    manufacturer: String,
+    brand: Option<String>,
+    group: Option<String>,
+}
+
+/// The accepted shapes of a JSON WMI file.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonFile {
+    List(Vec<JsonEntry>),
+    Map(HashMap<String, String>),
+}
+
+/// The embedded WMI table merged with entries loaded from files.
+#[derive(Debug, Clone, Default)]
+pub struct WmiDatabase {
+    embedded: HashMap<String, WmiEntry>,
+    external: HashMap<String, WmiEntry>,
+}
+
+impl WmiDatabase {
+    /// Creates a database containing only the embedded `wmi.csv` table.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vin::wmi_db::WmiDatabase;
+    ///
+    /// let db = WmiDatabase::embedded();
+    /// assert_eq!(db.manufacturer("1G1").unwrap(), "General Motors USA");
+    /// ```
+    pub fn embedded() -> Self {
+        let embedded = get_wmicsv()
+            .iter()
+            .map(|(wmi, manuf)| (wmi.clone(), WmiEntry::new(manuf, None, None)))
+            .collect();
+
+        WmiDatabase {
+            embedded,
+            external: HashMap::new(),
+        }
+    }
+
+    /// Loads a CSV or JSON file of extra WMI entries, overriding existing file entries.
+    ///
+    /// The format is chosen by the `.json` extension, anything else is read as CSV.
+    ///
+    /// # Returns
+    ///
+    /// The number of entries loaded from the file.
+    pub fn load_file<P: AsRef<Path>>(&mut self, path: P) -> Result<usize> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read WMI file: {}", path.display()))?;
+
+        let is_json = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+
+        let entries = if is_json {
+            parse_json(&content)
+        } else {
+            parse_csv(&content)
+        }
+        .with_context(|| format!("Failed to parse WMI file: {}", path.display()))?;
+
+        let count = entries.len();
+        self.external.extend(entries);
+
+        Ok(count)
+    }
+
+    /// Returns the entry used for a WMI, following the documented precedence.
+    ///
+    /// # Arguments
+    ///
+    /// * `wmi` - A 3- or 6-character WMI, as returned by [`crate::vin::wmi`]
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use vin::wmi_db::WmiDatabase;
+    ///
+    /// let db = WmiDatabase::embedded();
+    /// let entry = db.entry("TRU").unwrap();
+    /// assert_eq!(entry.manufacturer, "Audi Hungary");
+    /// assert_eq!(entry.brand.as_deref(), Some("Audi"));
+    /// assert_eq!(entry.group.as_deref(), Some("Volkswagen Group"));
+    /// ```
+    pub fn entry(&self, wmi: &str) -> Option<&WmiEntry> {
+        let prefix = |len: usize| wmi.get(..len).filter(|_| wmi.len() >= len);
+
+        [prefix(6), prefix(3), prefix(2)]
+            .into_iter()
+            .flatten()
+            .find_map(|key| self.external.get(key))
+            .or_else(|| {
+                prefix(2)
+                    .and_then(|key| self.embedded.get(key))
+                    .or_else(|| self.embedded.get(wmi))
+            })
+    }
+
+    /// Returns the manufacturer name for a WMI.
+    pub fn manufacturer(&self, wmi: &str) -> Option<&str> {
+        self.entry(wmi).map(|e| e.manufacturer.as_str())
+    }
+
+    /// Returns the brand for a WMI.
+    pub fn brand(&self, wmi: &str) -> Option<&str> {
+        self.entry(wmi).and_then(|e| e.brand.as_deref())
+    }
+
+    /// Returns the parent group for a WMI.
+    pub fn group(&self, wmi: &str) -> Option<&str> {
+        self.entry(wmi).and_then(|e| e.group.as_deref())
+    }
+}
+
+/// Initializes the global WMI database from `VIN_WMI_PATH` and the given WMI
+/// files, before the first lookup. See [`GlobalTable::init`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use shared::io::args;
+/// use vin::wmi_db::init_wmi_database;
+///
+/// // e.g. vin-manuf /etc/clickhouse-server/wmi_extra.csv
+/// init_wmi_database(args()).unwrap();
+/// ```
+pub fn init_wmi_database<I, P>(paths: I) -> Result<()>
+where
+    I: IntoIterator<Item = P>,
+    P: AsRef<Path>,
+{
+    WMI_DATABASE.init(paths)
+}
+
+/// Returns the global WMI database.
+///
+/// If [`init_wmi_database`] was not called, the database is built from the
+/// embedded table and the files listed in `VIN_WMI_PATH`.
+pub fn wmi_database() -> &'static WmiDatabase {
+    WMI_DATABASE.get()
+}
+
+fn parse_csv(content: &str) -> Result<Vec<(String, WmiEntry)>> {
+    let mut reader = ReaderBuilder::new()
+        .delimiter(b',')
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let headers = reader.headers()?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+
+    let (Some(wmi_col), Some(manuf_col)) = (column("wmi"), column("manufacturer")) else {
+        bail!("CSV header must contain WMI and Manufacturer columns");
+    };
+    let (brand_col, group_col) = (column("brand"), column("group"));
+
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let field = |col: Option<usize>| col.and_then(|c| record.get(c));
+
+        if let (Some(wmi), Some(manuf)) = (field(Some(wmi_col)), field(Some(manuf_col))) {
+            if !wmi.is_empty() && !manuf.is_empty() {
+                entries.push((
+                    wmi.to_uppercase(),
+                    WmiEntry::new(manuf, field(brand_col), field(group_col)),
+                ));
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+fn parse_json(content: &str) -> Result<Vec<(String, WmiEntry)>> {
+    let entries = match serde_json::from_str(content)? {
+        JsonFile::List(list) => list
+            .into_iter()
+            .map(|e| {
+                let entry = WmiEntry::new(&e.manufacturer, e.brand.as_deref(), e.group.as_deref());
+                (e.wmi.trim().to_uppercase(), entry)
+            })
+            .collect(),
+        JsonFile::Map(map) => map
+            .into_iter()
+            .map(|(wmi, manuf)| (wmi.trim().to_uppercase(), WmiEntry::new(&manuf, None, None)))
+            .collect(),
+    };
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_temp(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("vin-wmi-{}-{}", std::process::id(), name));
+        let mut file = std::fs::File::create(&path).unwrap();
+        file.write_all(content.as_bytes()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_embedded_brand_and_group() {
+        let db = WmiDatabase::embedded();
+
+        let entry = db.entry("1G1").unwrap();
+        assert_eq!(entry.manufacturer, "General Motors USA");
+        assert_eq!(entry.brand, None);
+        assert_eq!(entry.group.as_deref(), Some("General Motors"));
+
+        assert_eq!(db.brand("WMW"), Some("MINI"));
+        assert_eq!(db.group("WMW"), Some("BMW Group"));
+        assert_eq!(db.brand("SAL"), Some("Land Rover"));
+        assert_eq!(db.group("JTD"), Some("Toyota"));
+        assert_eq!(db.manufacturer("VF9795"), Some("Bugatti"));
+        assert_eq!(db.group("VF9795"), Some("Bugatti Rimac"));
+        assert!(db.entry("").is_none());
+    }
+
+    #[test]
+    fn test_csv_overrides() {
+        let path = write_temp(
+            "override.csv",
+            "WMI, Manufacturer, Brand, Group\n\
+             1g1, Chevrolet USA, Chevrolet, General Motors\n\
+             7SA, Tesla USA,,\n\
+             VF9795, Bugatti Automobiles,,\n",
+        );
+
+        let mut db = WmiDatabase::embedded();
+        assert_eq!(db.load_file(&path).unwrap(), 3);
+
+        // File entries win, most specific first
+        assert_eq!(db.manufacturer("1G1"), Some("Chevrolet USA"));
+        assert_eq!(db.brand("1G1"), Some("Chevrolet"));
+        assert_eq!(db.manufacturer("VF9795"), Some("Bugatti Automobiles"));
+        assert_eq!(db.group("VF9795"), Some("Bugatti Rimac"));
+
+        // Brand and group derived when missing
+        assert_eq!(db.brand("7SA"), Some("Tesla"));
+
+        // Embedded table still used for everything else
+        assert_eq!(db.manufacturer("1GK"), Some("General Motors USA"));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_json_overrides() {
+        let list = write_temp(
+            "list.json",
+            r#"[{"wmi": "LRW", "manufacturer": "Tesla China", "group": "Tesla"}]"#,
+        );
+        let map = write_temp("map.json", r#"{"LRW": "Tesla Shanghai", "XTA": "Lada"}"#);
+
+        let mut db = WmiDatabase::embedded();
+        db.load_file(&list).unwrap();
+        assert_eq!(db.manufacturer("LRW"), Some("Tesla China"));
+
+        // Later files override earlier ones
+        db.load_file(&map).unwrap();
+        assert_eq!(db.manufacturer("LRW"), Some("Tesla Shanghai"));
+        assert_eq!(db.group("XTA"), Some("AvtoVAZ"));
+
+        std::fs::remove_file(list).unwrap();
+        std::fs::remove_file(map).unwrap();
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let mut db = WmiDatabase::embedded();
+        assert!(db.load_file("/nonexistent/wmi.csv").is_err());
+
+        let bad = write_temp("bad.csv", "Code, Name\n1G1, Chevrolet\n");
+        assert!(db.load_file(&bad).is_err());
+        std::fs::remove_file(bad).unwrap();
+    }
+}